@@ -0,0 +1,42 @@
+#!/usr/bin/env python3
+"""Serves ledger entry fixtures as a stand-in for a ledger entry endpoint.
+
+Usage:
+    serve.py [port]           serve the fixtures directory (default port 8001)
+    serve.py add KEY ENTRY    add a fixture for a base64 LedgerKey and LedgerEntry
+"""
+
+import base64
+import hashlib
+import http.server
+import json
+import os
+import sys
+
+ROOT = os.path.dirname(os.path.abspath(__file__))
+
+
+class Handler(http.server.SimpleHTTPRequestHandler):
+    def __init__(self, *args, **kwargs):
+        super().__init__(*args, directory=ROOT, **kwargs)
+
+    def end_headers(self):
+        self.send_header("Access-Control-Allow-Origin", "*")
+        super().end_headers()
+
+
+def add(key, entry):
+    digest = hashlib.sha256(base64.b64decode(key)).hexdigest()
+    path = os.path.join(ROOT, "ledger_entries", f"{digest}.json")
+    with open(path, "w") as f:
+        json.dump({"key": key, "xdr": entry}, f, indent=2)
+        f.write("\n")
+    print(path)
+
+
+if __name__ == "__main__":
+    if len(sys.argv) == 4 and sys.argv[1] == "add":
+        add(sys.argv[2], sys.argv[3])
+    else:
+        port = int(sys.argv[1]) if len(sys.argv) > 1 else 8001
+        http.server.ThreadingHTTPServer(("", port), Handler).serve_forever()
@@ -0,0 +1,253 @@
+//! A cache of the events seen on a network, persisted in IndexedDB so that
+//! the history does not need to be fetched again when the page is reloaded.
+
+use std::{cell::RefCell, rc::Rc};
+
+use futures::channel::oneshot;
+use serde_derive::{Deserialize, Serialize};
+use stellar_xdr::Asset;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use crate::network::Network;
+use crate::stream::{Contract, Event, EventBody, Invocation, Order};
+
+const VERSION: u32 = 1;
+const EVENTS: &str = "events";
+const WASM: &str = "wasm";
+const CURSORS: &str = "cursors";
+
+/// An event as stored, with the WASM of deployments stored separately by hash
+/// so that contracts deployed many times are only stored once.
+#[derive(Serialize, Deserialize)]
+struct StoredEvent {
+    id: String,
+    tx: String,
+    at: String,
+    body: StoredEventBody,
+}
+
+#[derive(Serialize, Deserialize)]
+enum StoredEventBody {
+    Invocation(Invocation),
+    Deployment {
+        id: String,
+        hash: String,
+    },
+    Ed25519Deployment {
+        id: String,
+        hash: String,
+        key: [u8; 32],
+    },
+    TokenDeployment {
+        id: String,
+    },
+    AssetTokenDeployment {
+        id: String,
+        asset: Asset,
+    },
+}
+
+/// The paging tokens that the streams of events reached, between which every
+/// event has been stored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cursors {
+    /// The paging token the asc stream continues from.
+    pub newest: Option<String>,
+    /// The paging token the desc stream continues from.
+    pub oldest: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Cache {
+    db: IdbDatabase,
+}
+
+impl Cache {
+    /// Opens the cache of the network, creating it if it does not exist.
+    pub async fn open(network: &Network) -> Result<Self, String> {
+        let factory = web_sys::window()
+            .ok_or("no window")?
+            .indexed_db()
+            .map_err(js_error)?
+            .ok_or("indexeddb unavailable")?;
+        let name = format!("soroban-fiddle {}", network.horizon_url);
+        let open = factory.open_with_u32(&name, VERSION).map_err(js_error)?;
+        let upgrading = open.clone();
+        let onupgradeneeded = Closure::once(move || {
+            if let Ok(db) = upgrading.result() {
+                let db = db.unchecked_into::<IdbDatabase>();
+                for store in [EVENTS, WASM, CURSORS] {
+                    let _ = db.create_object_store(store);
+                }
+            }
+        });
+        open.set_onupgradeneeded(Some(onupgradeneeded.as_ref().unchecked_ref()));
+        let db = request(&open).await?.unchecked_into::<IdbDatabase>();
+        open.set_onupgradeneeded(None);
+        Ok(Self { db })
+    }
+
+    /// Returns every event stored, in no particular order.
+    pub async fn events(&self) -> Result<Vec<Event>, String> {
+        let values = request(&self.store(EVENTS, false)?.get_all().map_err(js_error)?).await?;
+        let mut events = vec![];
+        for v in js_sys::Array::from(&values).iter() {
+            let stored = match v.as_string().and_then(|s| serde_json::from_str(&s).ok()) {
+                Some(stored) => stored,
+                None => continue,
+            };
+            let StoredEvent { id, tx, at, body } = stored;
+            let body = match body {
+                StoredEventBody::Invocation(i) => EventBody::Invocation(i),
+                StoredEventBody::Deployment { id, hash } => match self.wasm(&hash).await? {
+                    Some(bytes) => EventBody::Deployment(Contract { id, bytes }),
+                    None => continue,
+                },
+                StoredEventBody::Ed25519Deployment { id, hash, key } => {
+                    match self.wasm(&hash).await? {
+                        Some(bytes) => EventBody::Ed25519Deployment {
+                            contract: Contract { id, bytes },
+                            key,
+                        },
+                        None => continue,
+                    }
+                }
+                StoredEventBody::TokenDeployment { id } => EventBody::TokenDeployment { id },
+                StoredEventBody::AssetTokenDeployment { id, asset } => {
+                    EventBody::AssetTokenDeployment { id, asset }
+                }
+            };
+            events.push(Event { id, tx, at, body });
+        }
+        Ok(events)
+    }
+
+    /// Stores the event, and advances the cursor of the stream of the order
+    /// to the event.
+    pub async fn put_event(&self, e: &Event, o: Order) -> Result<(), String> {
+        let body = match &e.body {
+            EventBody::Invocation(i) => StoredEventBody::Invocation(i.clone()),
+            EventBody::Deployment(c) => StoredEventBody::Deployment {
+                id: c.id.clone(),
+                hash: self.put_wasm(c).await?,
+            },
+            EventBody::Ed25519Deployment { contract, key } => StoredEventBody::Ed25519Deployment {
+                id: contract.id.clone(),
+                hash: self.put_wasm(contract).await?,
+                key: *key,
+            },
+            EventBody::TokenDeployment { id } => {
+                StoredEventBody::TokenDeployment { id: id.clone() }
+            }
+            EventBody::AssetTokenDeployment { id, asset } => {
+                StoredEventBody::AssetTokenDeployment {
+                    id: id.clone(),
+                    asset: asset.clone(),
+                }
+            }
+        };
+        let stored = StoredEvent {
+            id: e.id.clone(),
+            tx: e.tx.clone(),
+            at: e.at.clone(),
+            body,
+        };
+        let json = serde_json::to_string(&stored).map_err(|e| e.to_string())?;
+        self.put(EVENTS, &e.id, &json).await?;
+        self.put_cursor(o, &e.id).await
+    }
+
+    pub async fn cursors(&self) -> Result<Cursors, String> {
+        Ok(Cursors {
+            newest: self.get(CURSORS, cursor_key(Order::Asc)).await?,
+            oldest: self.get(CURSORS, cursor_key(Order::Desc)).await?,
+        })
+    }
+
+    /// Sets the paging token that the stream of the order continues from.
+    pub async fn put_cursor(&self, o: Order, cursor: &str) -> Result<(), String> {
+        self.put(CURSORS, cursor_key(o), cursor).await
+    }
+
+    /// Stores the WASM of the contract by its hash, returning the hash.
+    async fn put_wasm(&self, c: &Contract) -> Result<String, String> {
+        let hash = c.hash();
+        self.put(WASM, &hash, &base64::encode(&c.bytes)).await?;
+        Ok(hash)
+    }
+
+    async fn wasm(&self, hash: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self
+            .get(WASM, hash)
+            .await?
+            .and_then(|b64| base64::decode(b64).ok()))
+    }
+
+    async fn get(&self, store: &str, key: &str) -> Result<Option<String>, String> {
+        let req = self
+            .store(store, false)?
+            .get(&JsValue::from_str(key))
+            .map_err(js_error)?;
+        Ok(request(&req).await?.as_string())
+    }
+
+    async fn put(&self, store: &str, key: &str, value: &str) -> Result<(), String> {
+        let req = self
+            .store(store, true)?
+            .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))
+            .map_err(js_error)?;
+        request(&req).await.map(|_| ())
+    }
+
+    fn store(&self, store: &str, write: bool) -> Result<IdbObjectStore, String> {
+        let mode = if write {
+            IdbTransactionMode::Readwrite
+        } else {
+            IdbTransactionMode::Readonly
+        };
+        self.db
+            .transaction_with_str_and_mode(store, mode)
+            .and_then(|tx| tx.object_store(store))
+            .map_err(js_error)
+    }
+}
+
+fn cursor_key(o: Order) -> &'static str {
+    match o {
+        Order::Asc => "newest",
+        Order::Desc => "oldest",
+    }
+}
+
+/// Waits for the request to complete, returning its result.
+async fn request(req: &IdbRequest) -> Result<JsValue, String> {
+    let (tx, rx) = oneshot::channel();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let (tx_success, tx_error) = (tx.clone(), tx);
+    let (req_success, req_error) = (req.clone(), req.clone());
+    let onsuccess = Closure::once(move || {
+        if let Some(tx) = tx_success.borrow_mut().take() {
+            let _ = tx.send(req_success.result().map_err(js_error));
+        }
+    });
+    let onerror = Closure::once(move || {
+        if let Some(tx) = tx_error.borrow_mut().take() {
+            let error = match req_error.error() {
+                Ok(Some(e)) => e.message(),
+                _ => "unknown error".to_string(),
+            };
+            let _ = tx.send(Err(error));
+        }
+    });
+    req.set_onsuccess(Some(onsuccess.as_ref().unchecked_ref()));
+    req.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    let result = rx.await.map_err(|_| "request cancelled".to_string())?;
+    req.set_onsuccess(None);
+    req.set_onerror(None);
+    result
+}
+
+fn js_error(v: JsValue) -> String {
+    v.as_string().unwrap_or_else(|| format!("{v:?}"))
+}
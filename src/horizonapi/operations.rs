@@ -40,6 +40,9 @@ pub struct Record {
     pub footprint: Option<String>,
     pub funder: Option<String>,
     pub account: Option<String>,
+    /// The transaction of the operation, if it was requested to be joined.
+    #[serde(default)]
+    pub transaction: Option<super::transaction::Response>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -0,0 +1,40 @@
+use super::ledgerapi;
+use super::stream::{decode_xdr, get_json, Error};
+use stellar_xdr::{LedgerEntry, LedgerKey, WriteXdr};
+
+/// Returns the URL of a ledger entry, which is addressed by the SHA-256 hash
+/// of the XDR of its key.
+pub fn get_ledger_entry_url(base_url: &str, key: &LedgerKey) -> String {
+    format!(
+        "{base_url}/ledger_entries/{}.json",
+        sha256::digest(key.to_xdr().unwrap().as_slice())
+    )
+}
+
+pub async fn get_ledger_entries(
+    base_url: &str,
+    keys: &[LedgerKey],
+) -> Result<Vec<(LedgerKey, LedgerEntry)>, Error> {
+    let entries = futures::future::try_join_all(keys.iter().map(|key| async move {
+        Ok::<_, Error>(
+            get_ledger_entry(base_url, key)
+                .await?
+                .map(|entry| (key.clone(), entry)),
+        )
+    }))
+    .await?;
+    Ok(entries.into_iter().flatten().collect())
+}
+
+/// Gets the ledger entry for the key, returning none if it does not exist.
+pub async fn get_ledger_entry(
+    base_url: &str,
+    key: &LedgerKey,
+) -> Result<Option<LedgerEntry>, Error> {
+    let url = get_ledger_entry_url(base_url, key);
+    match get_json::<ledgerapi::entry::Response>(&url).await {
+        Ok(resp) => Ok(Some(decode_xdr("xdr", resp.xdr)?)),
+        Err(Error::Status { status: 404, .. }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
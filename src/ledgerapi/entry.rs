@@ -0,0 +1,7 @@
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub key: String,
+    pub xdr: String,
+}
@@ -1,22 +1,33 @@
-use ui::{history::SelectedEvent, invoke_info::InvokeInfoComp};
+use network::Network;
+use route::{Listener, Route};
+use ui::{history::SelectedEvent, invoke_info::InvokeInfoComp, network::NetworkComp};
 use yew::{
     prelude::{html, Component, Context, Html},
     start_app,
 };
 
+mod cache;
 mod horizonapi;
+mod ledger;
+mod ledgerapi;
+mod network;
+mod route;
+mod spec;
 mod stream;
+mod strkey;
+mod tx;
 mod ui;
 mod vm;
 
 use stream::EventBody;
 
+use crate::ui::contract::ContractComp;
 use crate::ui::contract_info::ContractInfoComp;
 use crate::ui::event_info::EventInfoComp;
 use crate::ui::history::HistoryComp;
 use crate::ui::invoke::InvokeComp;
-
-const HORIZON_BASE_URL: &str = "https://horizon-futurenet.stellar.org";
+use crate::ui::transaction::TransactionComp;
+use crate::ui::xdr::XdrComp;
 
 fn main() {
     start_app::<App>();
@@ -24,10 +35,15 @@ fn main() {
 
 #[derive(Default)]
 struct App {
+    network: Network,
+    route: Route,
+    _route_listener: Option<Listener>,
     selected_event: Option<SelectedEvent>,
 }
 
 enum AppMsg {
+    Route(Route),
+    SelectNetwork(Network),
     SelectEvent(SelectedEvent),
 }
 
@@ -35,12 +51,44 @@ impl Component for App {
     type Message = AppMsg;
     type Properties = ();
 
-    fn create(_ctx: &Context<Self>) -> Self {
-        Self::default()
+    fn create(ctx: &Context<Self>) -> Self {
+        let link = ctx.link().clone();
+        Self {
+            network: Network::load().unwrap_or_default(),
+            route: Route::current(),
+            _route_listener: Some(Route::listen(move |route| {
+                link.send_message(AppMsg::Route(route))
+            })),
+            ..Self::default()
+        }
     }
 
     fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            AppMsg::Route(route) => {
+                if self.route == route {
+                    return false;
+                }
+                if matches!(route, Route::Home | Route::Xdr) {
+                    self.selected_event = None;
+                }
+                self.route = route;
+                true
+            }
+            AppMsg::SelectNetwork(network) => {
+                if self.network == network {
+                    return false;
+                }
+                network.save();
+                // Changing only the ledger URL keeps the history and the
+                // selected event, since they come from Horizon.
+                if self.network.horizon_url != network.horizon_url {
+                    self.selected_event = None;
+                    Route::Home.navigate();
+                }
+                self.network = network;
+                true
+            }
             AppMsg::SelectEvent(e) => {
                 self.selected_event = Some(e);
                 true
@@ -51,31 +99,46 @@ impl Component for App {
     fn view(&self, ctx: &Context<Self>) -> Html {
         let scope = ctx.link();
         let onevent = { scope.callback(AppMsg::SelectEvent) };
+        let onnetwork = { scope.callback(AppMsg::SelectNetwork) };
+        let network = &self.network;
         html! {
             <>
-            <div class="header">{ "Soroban Fiddle – data from FutureNet (" }<a href="https://soroban.stellar.org">{ "soroban.stellar.org" }</a>{ ")" }</div>
+            <div class="header">
+                { "Soroban Fiddle – data from " }{ &network.name }{ " (" }<a href="https://soroban.stellar.org">{ "soroban.stellar.org" }</a>{ ") " }
+                <NetworkComp network={network.clone()} onchange={onnetwork} />
+                { " " }<a href={ Route::Xdr.to_string() }>{ "xdr" }</a>
+            </div>
             <div class="columns">
                 <div class="left">
-                    <HistoryComp {onevent} />
+                    <HistoryComp network={network.clone()} route={self.route.clone()} {onevent} />
                 </div>
                 <div class="right">
                 {
-                    if let Some(e) = &self.selected_event {
+                    if self.route == Route::Xdr {
+                        html! { <XdrComp /> }
+                    } else if let Some(e) = &self.selected_event {
                         html!{
                             <>
-                                <EventInfoComp event={e.event.clone()} />
+                                <EventInfoComp network={network.clone()} event={e.event.clone()} />
                                 {
                                     match &e.event.body {
                                         EventBody::Invocation(i) => html! {
                                             <>
-                                                <InvokeInfoComp invocation={i.clone()} />
+                                                <InvokeInfoComp network={network.clone()} event={e.event.clone()} invocation={i.clone()} contract={e.deployments.iter().find(|d| d.id == i.id).cloned()} related_events={e.related.clone()} deployments={e.deployments.clone()} />
+                                                <TransactionComp network={network.clone()} hash={e.event.tx.clone()} />
                                             </>
                                         },
-                                        EventBody::Deployment(c) => html! {
-                                            <>
-                                                <ContractInfoComp contract={c.clone()} />
-                                                <InvokeComp contract={c.clone()} event={e.event.clone()} related_events={e.related.clone()} />
-                                            </>
+                                        body => match body.deployed_contract() {
+                                            Some(c) => html! {
+                                                <>
+                                                    <ContractInfoComp contract={c.clone()} />
+                                                    <ContractComp network={network.clone()} contract={c.clone()} events={e.related.clone()} deployments={e.deployments.clone()} />
+                                                    <InvokeComp network={network.clone()} contract={c.clone()} event={e.event.clone()} related_events={e.related.clone()} deployments={e.deployments.clone()} />
+                                                </>
+                                            },
+                                            None => html! {
+                                                <TransactionComp network={network.clone()} hash={e.event.tx.clone()} />
+                                            },
                                         },
                                     }
                                 }
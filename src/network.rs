@@ -0,0 +1,110 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::vm::fee::FeeRates;
+
+const STORAGE_KEY: &str = "network";
+
+/// The ledger entry endpoint used until one is set, which is where the fixture
+/// server in this repo listens.
+pub const DEFAULT_LEDGER_URL: &str = "http://localhost:8001";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Network {
+    pub name: String,
+    pub horizon_url: String,
+    pub passphrase: String,
+    /// The URL of the ledger entry endpoint used to seed simulations.
+    pub ledger_url: String,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub fee_rates: FeeRates,
+}
+
+/// The resource limits that an invocation must fit within on a network.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Limits {
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            cpu_insns: 40_000_000,
+            mem_bytes: 50 * 1024 * 1024,
+        }
+    }
+}
+
+impl Network {
+    pub fn futurenet() -> Self {
+        Self {
+            name: "futurenet".to_string(),
+            horizon_url: "https://horizon-futurenet.stellar.org".to_string(),
+            passphrase: "Test SDF Future Network ; October 2022".to_string(),
+            ledger_url: DEFAULT_LEDGER_URL.to_string(),
+            limits: Limits::default(),
+            fee_rates: FeeRates::default(),
+        }
+    }
+
+    pub fn testnet() -> Self {
+        Self {
+            name: "testnet".to_string(),
+            horizon_url: "https://horizon-testnet.stellar.org".to_string(),
+            passphrase: "Test SDF Network ; September 2015".to_string(),
+            ledger_url: DEFAULT_LEDGER_URL.to_string(),
+            limits: Limits::default(),
+            fee_rates: FeeRates::default(),
+        }
+    }
+
+    pub fn standalone() -> Self {
+        Self {
+            name: "standalone".to_string(),
+            horizon_url: "http://localhost:8000".to_string(),
+            passphrase: "Standalone Network ; February 2017".to_string(),
+            ledger_url: DEFAULT_LEDGER_URL.to_string(),
+            limits: Limits::default(),
+            fee_rates: FeeRates::default(),
+        }
+    }
+
+    pub fn custom(horizon_url: String, passphrase: String, ledger_url: String) -> Self {
+        Self {
+            name: "custom".to_string(),
+            horizon_url,
+            passphrase,
+            ledger_url,
+            limits: Limits::default(),
+            fee_rates: FeeRates::default(),
+        }
+    }
+
+    pub fn presets() -> Vec<Network> {
+        vec![Self::futurenet(), Self::testnet(), Self::standalone()]
+    }
+
+    /// Loads the network last selected from local storage.
+    pub fn load() -> Option<Self> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        let json = storage.get_item(STORAGE_KEY).ok()??;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Saves the network to local storage so that it is selected on reload.
+    pub fn save(&self) {
+        if let Some(Ok(Some(storage))) = web_sys::window().map(|w| w.local_storage()) {
+            if let Ok(json) = serde_json::to_string(self) {
+                let _ = storage.set_item(STORAGE_KEY, &json);
+            }
+        }
+    }
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Self::futurenet()
+    }
+}
@@ -0,0 +1,89 @@
+use std::{fmt::Display, str::FromStr};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+
+/// A location in the app that can be linked to, stored in the hash of the URL
+/// as `#/tx/<hash>`, `#/contract/<id>`, `#/op/<id>` or `#/xdr`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Route {
+    Home,
+    Tx(String),
+    Contract(String),
+    Op(String),
+    /// The XDR playground.
+    Xdr,
+}
+
+impl Route {
+    /// Returns the route in the hash of the current URL.
+    pub fn current() -> Self {
+        web_sys::window()
+            .and_then(|w| w.location().hash().ok())
+            .and_then(|h| Route::from_str(&h).ok())
+            .unwrap_or_default()
+    }
+
+    /// Changes the hash of the current URL to the route, which triggers the
+    /// listener registered with [`Route::listen`].
+    pub fn navigate(&self) {
+        if let Some(w) = web_sys::window() {
+            let _ = w.location().set_hash(&self.to_string());
+        }
+    }
+
+    /// Calls f with the route every time the hash of the current URL changes.
+    /// The listener is removed when the returned listener is dropped.
+    pub fn listen(f: impl Fn(Route) + 'static) -> Listener {
+        let closure = Closure::wrap(Box::new(move || f(Route::current())) as Box<dyn Fn()>);
+        if let Some(w) = web_sys::window() {
+            let _ =
+                w.add_event_listener_with_callback("hashchange", closure.as_ref().unchecked_ref());
+        }
+        Listener(closure)
+    }
+}
+
+impl FromStr for Route {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_start_matches('#').trim_start_matches('/');
+        match s.split_once('/') {
+            None if s.is_empty() => Ok(Route::Home),
+            None if s == "xdr" => Ok(Route::Xdr),
+            Some(("tx", hash)) if !hash.is_empty() => Ok(Route::Tx(hash.to_string())),
+            Some(("contract", id)) if !id.is_empty() => Ok(Route::Contract(id.to_string())),
+            Some(("op", id)) if !id.is_empty() => Ok(Route::Op(id.to_string())),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Route::Home => write!(f, "#/"),
+            Route::Tx(hash) => write!(f, "#/tx/{hash}"),
+            Route::Contract(id) => write!(f, "#/contract/{id}"),
+            Route::Op(id) => write!(f, "#/op/{id}"),
+            Route::Xdr => write!(f, "#/xdr"),
+        }
+    }
+}
+
+impl Default for Route {
+    fn default() -> Self {
+        Route::Home
+    }
+}
+
+pub struct Listener(Closure<dyn Fn()>);
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Some(w) = web_sys::window() {
+            let _ = w
+                .remove_event_listener_with_callback("hashchange", self.0.as_ref().unchecked_ref());
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Decoding of values into readable strings, using a contract spec for the
+//! types of values where they are known.
+
+use serde::Serialize;
+use stellar_xdr::{
+    AccountId, AlphaNum12, AlphaNum4, Asset, ContractDataEntry, ContractEvent, ContractEventBody,
+    ContractEventV0, Int128Parts, LedgerEntry, LedgerEntryData, LedgerKey, LedgerKeyContractData,
+    OperationResult, OperationResultTr, PublicKey, ScMap, ScObject, ScSpecEntry, ScSpecTypeDef,
+    ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeSet, ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec,
+    ScStatic, ScVal, ScVec, TransactionResultResult, Uint256,
+};
+
+use super::{udt, Udt};
+use crate::strkey;
+
+/// Returns the value as a string, decoded as the type if the value matches
+/// the type, otherwise decoded by the shape of the value alone.
+pub fn to_string(v: &ScVal, t: &ScSpecTypeDef, entries: &[ScSpecEntry]) -> String {
+    typed(v, t, entries).unwrap_or_else(|| untyped(v))
+}
+
+fn typed(v: &ScVal, t: &ScSpecTypeDef, entries: &[ScSpecEntry]) -> Option<String> {
+    Some(match (t, v) {
+        (ScSpecTypeDef::Val, v) => untyped(v),
+        (ScSpecTypeDef::U32, ScVal::U32(i)) => i.to_string(),
+        (ScSpecTypeDef::I32, ScVal::I32(i)) => i.to_string(),
+        (ScSpecTypeDef::U64, ScVal::U63(i)) => i.to_string(),
+        (ScSpecTypeDef::U64, ScVal::Object(Some(ScObject::U64(i)))) => i.to_string(),
+        (ScSpecTypeDef::I64, ScVal::U63(i)) => i.to_string(),
+        (ScSpecTypeDef::I64, ScVal::Object(Some(ScObject::I64(i)))) => i.to_string(),
+        (ScSpecTypeDef::U128, ScVal::Object(Some(ScObject::U128(p)))) => u128_from(p).to_string(),
+        (ScSpecTypeDef::I128, ScVal::Object(Some(ScObject::I128(p)))) => i128_from(p).to_string(),
+        (ScSpecTypeDef::Bool, ScVal::Static(ScStatic::True)) => "true".to_string(),
+        (ScSpecTypeDef::Bool, ScVal::Static(ScStatic::False)) => "false".to_string(),
+        (ScSpecTypeDef::Symbol, ScVal::Symbol(s)) => format!("{:?}", s.to_string_lossy()),
+        (ScSpecTypeDef::Bitset, ScVal::Bitset(b)) => format!("{b:#b}"),
+        (
+            ScSpecTypeDef::Bytes | ScSpecTypeDef::BytesN(_),
+            ScVal::Object(Some(ScObject::Bytes(b))),
+        ) => hex::encode(b),
+        (ScSpecTypeDef::AccountId, ScVal::Object(Some(ScObject::AccountId(a)))) => account_id(a),
+        (ScSpecTypeDef::Option(_), ScVal::Static(ScStatic::Void)) => "None".to_string(),
+        (ScSpecTypeDef::Option(o), v) => {
+            let ScSpecTypeOption { value_type } = o.as_ref();
+            format!("Some({})", typed(v, value_type, entries)?)
+        }
+        (ScSpecTypeDef::Vec(t), ScVal::Object(Some(ScObject::Vec(ScVec(vals))))) => {
+            let ScSpecTypeVec { element_type } = t.as_ref();
+            list(vals.iter().map(|v| typed(v, element_type, entries)))?
+        }
+        (ScSpecTypeDef::Set(t), ScVal::Object(Some(ScObject::Vec(ScVec(vals))))) => {
+            let ScSpecTypeSet { element_type } = t.as_ref();
+            list(vals.iter().map(|v| typed(v, element_type, entries)))?
+        }
+        (ScSpecTypeDef::Tuple(t), ScVal::Object(Some(ScObject::Vec(ScVec(vals))))) => {
+            let ScSpecTypeTuple { value_types } = t.as_ref();
+            if vals.len() != value_types.len() {
+                return None;
+            }
+            let items = vals
+                .iter()
+                .zip(value_types.iter())
+                .map(|(v, t)| typed(v, t, entries))
+                .collect::<Option<Vec<_>>>()?;
+            format!("({})", items.join(", "))
+        }
+        (ScSpecTypeDef::Map(t), ScVal::Object(Some(ScObject::Map(ScMap(map_entries))))) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = t.as_ref();
+            let items = map_entries
+                .iter()
+                .map(|e| {
+                    Some(format!(
+                        "{}: {}",
+                        typed(&e.key, key_type, entries)?,
+                        typed(&e.val, value_type, entries)?
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            format!("{{{}}}", items.join(", "))
+        }
+        (ScSpecTypeDef::Udt(ScSpecTypeUdt { name }), v) => {
+            let name = name.to_string_lossy();
+            match (udt(entries, &name)?, v) {
+                (Udt::Struct(s), ScVal::Object(Some(ScObject::Vec(ScVec(vals))))) => {
+                    if vals.len() != s.fields.len() {
+                        return None;
+                    }
+                    let items = vals
+                        .iter()
+                        .zip(s.fields.iter())
+                        .map(|(v, f)| typed(v, &f.type_, entries))
+                        .collect::<Option<Vec<_>>>()?;
+                    format!("{name}({})", items.join(", "))
+                }
+                (Udt::Struct(s), ScVal::Object(Some(ScObject::Map(ScMap(map))))) => {
+                    let items = s
+                        .fields
+                        .iter()
+                        .map(|f| {
+                            let field = f.name.to_string_lossy();
+                            let e = map.iter().find(
+                                |e| matches!(&e.key, ScVal::Symbol(k) if k.to_string_lossy() == field),
+                            )?;
+                            Some(format!("{field}: {}", typed(&e.val, &f.type_, entries)?))
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    format!("{name} {{ {} }}", items.join(", "))
+                }
+                (Udt::Union(u), ScVal::Object(Some(ScObject::Vec(ScVec(vals))))) => {
+                    let case = match vals.first() {
+                        Some(ScVal::Symbol(case)) => case.to_string_lossy(),
+                        _ => return None,
+                    };
+                    let c = u.cases.iter().find(|c| c.name.to_string_lossy() == case)?;
+                    match (&c.type_, vals.get(1)) {
+                        (None, None) => format!("{name}::{case}"),
+                        (Some(t), Some(v)) => format!("{name}::{case}({})", typed(v, t, entries)?),
+                        _ => return None,
+                    }
+                }
+                (Udt::Enum(e), ScVal::U32(i)) => {
+                    let c = e.cases.iter().find(|c| c.value == *i)?;
+                    format!("{name}::{}", c.name.to_string_lossy())
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Returns the value as a string, decoded by the shape of the value alone.
+pub fn untyped(v: &ScVal) -> String {
+    match v {
+        ScVal::U63(i) => i.to_string(),
+        ScVal::U32(i) => format!("{i}u32"),
+        ScVal::I32(i) => format!("{i}i32"),
+        ScVal::Static(ScStatic::True) => "true".to_string(),
+        ScVal::Static(ScStatic::False) => "false".to_string(),
+        ScVal::Static(ScStatic::Void) => "()".to_string(),
+        ScVal::Static(s) => format!("{s:?}"),
+        ScVal::Symbol(s) => format!("{:?}", s.to_string_lossy()),
+        ScVal::Bitset(b) => format!("{b:#b}"),
+        ScVal::Status(s) => format!("{s:?}"),
+        ScVal::Object(None) => "null".to_string(),
+        ScVal::Object(Some(o)) => match o {
+            ScObject::Vec(ScVec(vals)) => {
+                format!(
+                    "[{}]",
+                    vals.iter().map(untyped).collect::<Vec<_>>().join(", ")
+                )
+            }
+            ScObject::Map(ScMap(entries)) => format!(
+                "{{{}}}",
+                entries
+                    .iter()
+                    .map(|e| format!("{}: {}", untyped(&e.key), untyped(&e.val)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ScObject::U64(i) => format!("{i}u64"),
+            ScObject::I64(i) => format!("{i}i64"),
+            ScObject::U128(p) => format!("{}u128", u128_from(p)),
+            ScObject::I128(p) => format!("{}i128", i128_from(p)),
+            ScObject::Bytes(b) => format!("0x{}", hex::encode(b)),
+            ScObject::ContractCode(_) => "<contract code>".to_string(),
+            ScObject::AccountId(a) => account_id(a),
+        },
+    }
+}
+
+/// Returns the contract event as a string, with its topics and data decoded
+/// by the shape of the values alone.
+pub fn event(e: &ContractEvent) -> String {
+    let ContractEventBody::V0(ContractEventV0 {
+        topics: ScVec(topics),
+        data,
+    }) = &e.body;
+    format!(
+        "contract: {}\ntopics: [{}]\ndata: {}",
+        e.contract_id
+            .as_ref()
+            .map(|id| hex::encode(id.0))
+            .unwrap_or_default(),
+        topics.iter().map(untyped).collect::<Vec<_>>().join(", "),
+        untyped(data),
+    )
+}
+
+/// Returns the ledger key as a string, with the key of contract data decoded
+/// by the shape of the value alone.
+pub fn ledger_key(k: &LedgerKey) -> String {
+    match k {
+        LedgerKey::ContractData(LedgerKeyContractData { contract_id, key }) => {
+            format!("{}: {}", hex::encode(contract_id.0), untyped(key))
+        }
+        _ => serde_json::to_string(k).unwrap_or_default(),
+    }
+}
+
+/// Returns the value of the ledger entry as a string, with the value of
+/// contract data decoded by the shape of the value alone.
+pub fn ledger_entry(e: &LedgerEntry) -> String {
+    match &e.data {
+        LedgerEntryData::ContractData(ContractDataEntry { val, .. }) => untyped(val),
+        data => serde_json::to_string(data).unwrap_or_default(),
+    }
+}
+
+/// Returns the asset as native, or as its code and issuer.
+pub fn asset(a: &Asset) -> String {
+    match a {
+        Asset::Native => "native".to_string(),
+        Asset::CreditAlphanum4(AlphaNum4 { asset_code, issuer }) => {
+            format!(
+                "{}:{}",
+                asset_code_string(&asset_code.0),
+                account_id(issuer)
+            )
+        }
+        Asset::CreditAlphanum12(AlphaNum12 { asset_code, issuer }) => {
+            format!(
+                "{}:{}",
+                asset_code_string(&asset_code.0),
+                account_id(issuer)
+            )
+        }
+    }
+}
+
+/// Returns a readable explanation of why the transaction failed, and if it
+/// failed because of its operations, why each of them failed.
+pub fn failure(r: &TransactionResultResult) -> String {
+    let code = code(r);
+    let reason = match code.as_str() {
+        "tx_failed" => "one or more operations failed",
+        "tx_too_early" => "the transaction was submitted before its time bounds",
+        "tx_too_late" => "the transaction was submitted after its time bounds",
+        "tx_missing_operation" => "the transaction has no operations",
+        "tx_bad_seq" => "the sequence number does not follow the source account's",
+        "tx_bad_auth" => "too few valid signatures, or signed for another network",
+        "tx_insufficient_balance" => "the fee would take the source account below its reserve",
+        "tx_no_account" => "the source account does not exist",
+        "tx_insufficient_fee" => "the fee is less than the network requires",
+        "tx_bad_auth_extra" => "the transaction has signatures that are not needed",
+        "tx_internal_error" => "an unknown error occurred in the network",
+        "tx_not_supported" => "the transaction type is not supported",
+        "tx_malformed" => "the transaction is malformed",
+        _ => "the transaction failed",
+    };
+    match r {
+        TransactionResultResult::TxFailed(ops) => {
+            let ops = ops
+                .iter()
+                .enumerate()
+                .map(|(i, op)| format!("\nop {i}: {}", operation_failure(op)))
+                .collect::<String>();
+            format!("{code}: {reason}{ops}")
+        }
+        _ => format!("{code}: {reason}"),
+    }
+}
+
+fn operation_failure(r: &OperationResult) -> String {
+    match r {
+        OperationResult::OpInner(OperationResultTr::InvokeHostFunction(r)) => {
+            let code = code(r);
+            let reason = match code.as_str() {
+                "success" => "succeeded, but was rolled back when the transaction failed",
+                "malformed" => "the host function or its parameters are malformed",
+                "trapped" => {
+                    "the contract trapped, by panicking, returning an error, or failing a host \
+                     function call"
+                }
+                "resource_limit_exceeded" => {
+                    "the invocation exceeded the network's cpu or memory limits"
+                }
+                _ => "the invocation failed",
+            };
+            format!("{code}: {reason}")
+        }
+        r => serde_json::to_string(r).unwrap_or_default(),
+    }
+}
+
+/// Returns the name of the variant of the XDR union or enum.
+fn code(v: &impl Serialize) -> String {
+    match serde_json::to_value(v) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(serde_json::Value::Object(o)) => o.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn list(items: impl Iterator<Item = Option<String>>) -> Option<String> {
+    Some(format!(
+        "[{}]",
+        items.collect::<Option<Vec<_>>>()?.join(", ")
+    ))
+}
+
+fn u128_from(p: &Int128Parts) -> u128 {
+    (u128::from(p.hi) << 64) | u128::from(p.lo)
+}
+
+fn i128_from(p: &Int128Parts) -> i128 {
+    u128_from(p) as i128
+}
+
+fn asset_code_string(code: &[u8]) -> String {
+    String::from_utf8_lossy(code)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+fn account_id(a: &AccountId) -> String {
+    let AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key))) = a;
+    strkey::encode_public_key(key)
+}
@@ -0,0 +1,425 @@
+//! Encoding of values typed by a contract spec from JSON, which is what the
+//! inputs of the args form are assembled into. Scalars are strings of the text
+//! entered, or JSON numbers and booleans. Compound values take the forms:
+//!
+//! - `Vec`, `Set` and tuples: `[v, ...]`
+//! - `Map`: `[[k, v], ...]`
+//! - `Option`: `null` for none, otherwise the value
+//! - structs: `{"field": v, ...}`, or `[v, ...]` for tuple structs
+//! - unions: `"Case"` for a case without a value, otherwise `{"Case": v}`
+//! - enums: `"Case"` or the case's integer value
+
+use serde_json::Value;
+use stellar_xdr::{
+    AccountId, Int128Parts, PublicKey, ScMap, ScMapEntry, ScObject, ScSpecEntry, ScSpecTypeDef,
+    ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeSet, ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec,
+    ScSpecUdtEnumCaseV0, ScSpecUdtStructFieldV0, ScSpecUdtUnionCaseV0, ScStatic, ScVal, ScVec,
+    Uint256,
+};
+
+use super::{input::Input, type_name, udt, Udt};
+use crate::strkey;
+
+/// Encodes the input entered for a value of the type.
+pub fn from_input(
+    input: &Input,
+    t: &ScSpecTypeDef,
+    entries: &[ScSpecEntry],
+) -> Result<ScVal, String> {
+    from_json(&input.to_json(t, entries)?, t, entries)
+}
+
+/// Encodes the JSON value as a value of the type.
+pub fn from_json(v: &Value, t: &ScSpecTypeDef, entries: &[ScSpecEntry]) -> Result<ScVal, String> {
+    let expected = || format!("expected {}, got {v}", type_name(t));
+    match t {
+        ScSpecTypeDef::Val => serde_json::from_value(v.clone()).map_err(|e| e.to_string()),
+        ScSpecTypeDef::U32 => Ok(ScVal::U32(int(v)?.try_into().map_err(|_| expected())?)),
+        ScSpecTypeDef::I32 => Ok(ScVal::I32(int(v)?.try_into().map_err(|_| expected())?)),
+        ScSpecTypeDef::U64 => Ok(ScVal::Object(Some(ScObject::U64(
+            int(v)?.try_into().map_err(|_| expected())?,
+        )))),
+        ScSpecTypeDef::I64 => {
+            let i: i64 = int(v)?.try_into().map_err(|_| expected())?;
+            if i >= 0 {
+                Ok(ScVal::U63(i))
+            } else {
+                Ok(ScVal::Object(Some(ScObject::I64(i))))
+            }
+        }
+        ScSpecTypeDef::U128 => {
+            let i: u128 = match v {
+                Value::String(s) => s.replace('_', "").parse().map_err(|_| expected())?,
+                _ => int(v)?.try_into().map_err(|_| expected())?,
+            };
+            Ok(ScVal::Object(Some(ScObject::U128(Int128Parts {
+                lo: i as u64,
+                hi: (i >> 64) as u64,
+            }))))
+        }
+        ScSpecTypeDef::I128 => {
+            let i = int(v)?;
+            Ok(ScVal::Object(Some(ScObject::I128(Int128Parts {
+                lo: i as u64,
+                hi: ((i as u128) >> 64) as u64,
+            }))))
+        }
+        ScSpecTypeDef::Bitset => Ok(ScVal::Bitset(int(v)?.try_into().map_err(|_| expected())?)),
+        ScSpecTypeDef::Bool => match v {
+            Value::Bool(true) => Ok(ScVal::Static(ScStatic::True)),
+            Value::Bool(false) => Ok(ScVal::Static(ScStatic::False)),
+            Value::String(s) if s == "true" => Ok(ScVal::Static(ScStatic::True)),
+            Value::String(s) if s == "false" => Ok(ScVal::Static(ScStatic::False)),
+            _ => Err(expected()),
+        },
+        ScSpecTypeDef::Symbol => {
+            let s = string(v)?;
+            if !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("symbol has invalid characters: {s}"));
+            }
+            Ok(ScVal::Symbol((&s).try_into().map_err(|_| {
+                format!("symbol longer than 10 characters: {s}")
+            })?))
+        }
+        ScSpecTypeDef::Bytes => Ok(ScVal::Object(Some(ScObject::Bytes(
+            bytes(v)?.try_into().map_err(|_| expected())?,
+        )))),
+        ScSpecTypeDef::BytesN(b) => {
+            let bytes = bytes(v)?;
+            if bytes.len() != b.n as usize {
+                return Err(format!("expected {} bytes, got {}", b.n, bytes.len()));
+            }
+            Ok(ScVal::Object(Some(ScObject::Bytes(
+                bytes.try_into().map_err(|_| expected())?,
+            ))))
+        }
+        ScSpecTypeDef::AccountId => {
+            let s = string(v)?;
+            let key = if s.starts_with('G') {
+                strkey::decode_public_key(&s)?
+            } else {
+                hex::decode(&s)
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| format!("expected G... strkey or 32 byte hex, got {s}"))?
+            };
+            Ok(ScVal::Object(Some(ScObject::AccountId(AccountId(
+                PublicKey::PublicKeyTypeEd25519(Uint256(key)),
+            )))))
+        }
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = o.as_ref();
+            if v.is_null() {
+                Ok(ScVal::Static(ScStatic::Void))
+            } else {
+                from_json(v, value_type, entries)
+            }
+        }
+        ScSpecTypeDef::Vec(t) => {
+            let ScSpecTypeVec { element_type } = t.as_ref();
+            vec(array(v)?
+                .iter()
+                .map(|v| from_json(v, element_type, entries))
+                .collect::<Result<_, _>>()?)
+        }
+        ScSpecTypeDef::Set(t) => {
+            let ScSpecTypeSet { element_type } = t.as_ref();
+            vec(array(v)?
+                .iter()
+                .map(|v| from_json(v, element_type, entries))
+                .collect::<Result<_, _>>()?)
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = t.as_ref();
+            let values = array(v)?;
+            if values.len() != value_types.len() {
+                return Err(expected());
+            }
+            vec(values
+                .iter()
+                .zip(value_types.iter())
+                .map(|(v, t)| from_json(v, t, entries))
+                .collect::<Result<_, _>>()?)
+        }
+        ScSpecTypeDef::Map(t) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = t.as_ref();
+            map(array(v)?
+                .iter()
+                .map(|e| match e.as_array().map(Vec::as_slice) {
+                    Some([k, v]) => Ok(ScMapEntry {
+                        key: from_json(k, key_type, entries)?,
+                        val: from_json(v, value_type, entries)?,
+                    }),
+                    _ => Err(format!("expected [key, value], got {e}")),
+                })
+                .collect::<Result<_, _>>()?)
+        }
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+            let name = name.to_string_lossy();
+            match udt(entries, &name) {
+                Some(Udt::Struct(s)) => from_json_struct(v, &s.fields, entries),
+                Some(Udt::Union(u)) => from_json_union(v, &u.cases, entries),
+                Some(Udt::Enum(e)) => from_json_enum(v, &e.cases),
+                None => Err(format!("type {name} not found in the contract spec")),
+            }
+        }
+        ScSpecTypeDef::Status | ScSpecTypeDef::Invoker | ScSpecTypeDef::Result(_) => {
+            Err(format!("{} values are not supported", type_name(t)))
+        }
+    }
+}
+
+fn from_json_struct(
+    v: &Value,
+    fields: &[ScSpecUdtStructFieldV0],
+    entries: &[ScSpecEntry],
+) -> Result<ScVal, String> {
+    // Tuple structs have fields named by their position, and are vecs.
+    if fields
+        .iter()
+        .all(|f| f.name.to_string_lossy().parse::<u32>().is_ok())
+    {
+        let values = array(v)?;
+        if values.len() != fields.len() {
+            return Err(format!("expected {} values, got {v}", fields.len()));
+        }
+        return vec(values
+            .iter()
+            .zip(fields.iter())
+            .map(|(v, f)| from_json(v, &f.type_, entries))
+            .collect::<Result<_, _>>()?);
+    }
+    let object = v
+        .as_object()
+        .ok_or_else(|| format!("expected object, got {v}"))?;
+    map(fields
+        .iter()
+        .map(|f| {
+            let name = f.name.to_string_lossy();
+            let v = object
+                .get(&name)
+                .ok_or_else(|| format!("missing field {name}"))?;
+            Ok(ScMapEntry {
+                key: ScVal::Symbol((&name).try_into().map_err(|_| "invalid field name")?),
+                val: from_json(v, &f.type_, entries).map_err(|e| format!("{name}: {e}"))?,
+            })
+        })
+        .collect::<Result<_, String>>()?)
+}
+
+fn from_json_union(
+    v: &Value,
+    cases: &[ScSpecUdtUnionCaseV0],
+    entries: &[ScSpecEntry],
+) -> Result<ScVal, String> {
+    let (name, value) = match v {
+        Value::String(name) => (name.clone(), None),
+        Value::Object(o) if o.len() == 1 => {
+            let (name, value) = o.iter().next().unwrap();
+            (name.clone(), Some(value))
+        }
+        _ => return Err(format!("expected \"Case\" or {{\"Case\": value}}, got {v}")),
+    };
+    let case = cases
+        .iter()
+        .find(|c| c.name.to_string_lossy() == name)
+        .ok_or_else(|| format!("unknown case {name}"))?;
+    let mut values = vec![ScVal::Symbol(
+        (&name).try_into().map_err(|_| "invalid case name")?,
+    )];
+    match (&case.type_, value) {
+        (None, None) => {}
+        (Some(t), Some(value)) => values.push(from_json(value, t, entries)?),
+        (None, Some(_)) => return Err(format!("case {name} has no value")),
+        (Some(_), None) => return Err(format!("case {name} requires a value")),
+    }
+    vec(values)
+}
+
+fn from_json_enum(v: &Value, cases: &[ScSpecUdtEnumCaseV0]) -> Result<ScVal, String> {
+    let case = match v {
+        Value::String(s) => cases
+            .iter()
+            .find(|c| c.name.to_string_lossy() == *s || c.value.to_string() == *s),
+        Value::Number(n) => cases.iter().find(|c| n.as_u64() == Some(c.value.into())),
+        _ => None,
+    };
+    case.map(|c| ScVal::U32(c.value))
+        .ok_or_else(|| format!("unknown case {v}"))
+}
+
+fn vec(values: Vec<ScVal>) -> Result<ScVal, String> {
+    Ok(ScVal::Object(Some(ScObject::Vec(ScVec(
+        values.try_into().map_err(|_| "too many values")?,
+    )))))
+}
+
+fn map(mut entries: Vec<ScMapEntry>) -> Result<ScVal, String> {
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(ScVal::Object(Some(ScObject::Map(ScMap(
+        entries.try_into().map_err(|_| "too many entries")?,
+    )))))
+}
+
+fn int(v: &Value) -> Result<i128, String> {
+    match v {
+        Value::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .ok_or_else(|| format!("expected integer, got {n}")),
+        Value::String(s) => s
+            .replace('_', "")
+            .parse()
+            .map_err(|_| format!("expected integer, got {s}")),
+        _ => Err(format!("expected integer, got {v}")),
+    }
+}
+
+fn string(v: &Value) -> Result<String, String> {
+    v.as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("expected string, got {v}"))
+}
+
+fn bytes(v: &Value) -> Result<Vec<u8>, String> {
+    let s = string(v)?;
+    hex::decode(s.trim_start_matches("0x")).map_err(|_| format!("expected hex, got {s}"))
+}
+
+fn array(v: &Value) -> Result<&Vec<Value>, String> {
+    v.as_array()
+        .ok_or_else(|| format!("expected array, got {v}"))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn symbol(s: &str) -> ScVal {
+        ScVal::Symbol(s.try_into().unwrap())
+    }
+
+    fn int128(lo: u64, hi: u64) -> ScVal {
+        ScVal::Object(Some(ScObject::I128(Int128Parts { lo, hi })))
+    }
+
+    #[test]
+    fn test_i128() {
+        let t = ScSpecTypeDef::I128;
+        assert_eq!(from_json(&json!(1), &t, &[]), Ok(int128(1, 0)));
+        assert_eq!(
+            from_json(&json!(-1), &t, &[]),
+            Ok(int128(u64::MAX, u64::MAX))
+        );
+        assert_eq!(
+            from_json(&json!("-2"), &t, &[]),
+            Ok(int128(u64::MAX - 1, u64::MAX))
+        );
+        assert_eq!(
+            from_json(&json!(i128::MAX.to_string()), &t, &[]),
+            Ok(int128(u64::MAX, u64::MAX >> 1))
+        );
+        assert_eq!(
+            from_json(&json!(i128::MIN.to_string()), &t, &[]),
+            Ok(int128(0, 1 << 63))
+        );
+    }
+
+    #[test]
+    fn test_struct() {
+        let fields = [
+            ScSpecUdtStructFieldV0 {
+                name: "b".try_into().unwrap(),
+                type_: ScSpecTypeDef::Bool,
+            },
+            ScSpecUdtStructFieldV0 {
+                name: "a".try_into().unwrap(),
+                type_: ScSpecTypeDef::U32,
+            },
+        ];
+        // Fields are encoded into a map sorted by key, whatever their order.
+        assert_eq!(
+            from_json_struct(&json!({"b": true, "a": 1}), &fields, &[]),
+            map(vec![
+                ScMapEntry {
+                    key: symbol("a"),
+                    val: ScVal::U32(1),
+                },
+                ScMapEntry {
+                    key: symbol("b"),
+                    val: ScVal::Static(ScStatic::True),
+                },
+            ])
+        );
+        assert!(from_json_struct(&json!({"b": true}), &fields, &[]).is_err());
+    }
+
+    #[test]
+    fn test_tuple_struct() {
+        let fields = [
+            ScSpecUdtStructFieldV0 {
+                name: "0".try_into().unwrap(),
+                type_: ScSpecTypeDef::U32,
+            },
+            ScSpecUdtStructFieldV0 {
+                name: "1".try_into().unwrap(),
+                type_: ScSpecTypeDef::Symbol,
+            },
+        ];
+        assert_eq!(
+            from_json_struct(&json!([2, "abc"]), &fields, &[]),
+            vec(vec![ScVal::U32(2), symbol("abc")])
+        );
+        assert!(from_json_struct(&json!([2]), &fields, &[]).is_err());
+    }
+
+    #[test]
+    fn test_union() {
+        let cases = [
+            ScSpecUdtUnionCaseV0 {
+                name: "Unit".try_into().unwrap(),
+                type_: None,
+            },
+            ScSpecUdtUnionCaseV0 {
+                name: "Value".try_into().unwrap(),
+                type_: Some(ScSpecTypeDef::U32),
+            },
+        ];
+        assert_eq!(
+            from_json_union(&json!("Unit"), &cases, &[]),
+            vec(vec![symbol("Unit")])
+        );
+        assert_eq!(
+            from_json_union(&json!({"Value": 2}), &cases, &[]),
+            vec(vec![symbol("Value"), ScVal::U32(2)])
+        );
+        assert!(from_json_union(&json!({"Unit": 1}), &cases, &[]).is_err());
+        assert!(from_json_union(&json!("Value"), &cases, &[]).is_err());
+        assert!(from_json_union(&json!("Other"), &cases, &[]).is_err());
+    }
+
+    #[test]
+    fn test_enum() {
+        let cases = [
+            ScSpecUdtEnumCaseV0 {
+                name: "A".try_into().unwrap(),
+                value: 1,
+            },
+            ScSpecUdtEnumCaseV0 {
+                name: "B".try_into().unwrap(),
+                value: 2,
+            },
+        ];
+        assert_eq!(from_json_enum(&json!("B"), &cases), Ok(ScVal::U32(2)));
+        assert_eq!(from_json_enum(&json!("1"), &cases), Ok(ScVal::U32(1)));
+        assert_eq!(from_json_enum(&json!(2), &cases), Ok(ScVal::U32(2)));
+        assert!(from_json_enum(&json!("C"), &cases).is_err());
+        assert!(from_json_enum(&json!(3), &cases).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! The inputs of a form for entering a value typed by a contract spec. Inputs
+//! are shaped like the type, so that compound values are entered one field,
+//! case or element at a time, and are assembled into the JSON accepted by
+//! [`super::encode::from_json`].
+
+use serde_json::{Map, Value};
+use stellar_xdr::{
+    ScSpecEntry, ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeSet, ScSpecTypeTuple,
+    ScSpecTypeUdt, ScSpecTypeVec, ScSpecUdtUnionCaseV0,
+};
+
+use super::{type_name, udt, Udt};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    /// Text entered for a scalar or a raw value, or the case of an enum.
+    Text(String),
+    /// An input for each field of a struct or element of a tuple, or for the
+    /// key and value of a map entry.
+    Fields(Vec<Input>),
+    /// The case of a union, with an input for its value if it has one.
+    Case(String, Option<Box<Input>>),
+    /// An option, with an input for its value if it is some.
+    Option(Option<Box<Input>>),
+    /// A row for each element of a vec or set, or each entry of a map.
+    Rows(Vec<Input>),
+}
+
+impl Input {
+    /// Returns the input for a value of the type before anything is entered.
+    pub fn new(t: &ScSpecTypeDef, entries: &[ScSpecEntry]) -> Self {
+        match t {
+            ScSpecTypeDef::Bool => Input::Text("false".to_string()),
+            ScSpecTypeDef::Option(_) => Input::Option(None),
+            ScSpecTypeDef::Vec(_) | ScSpecTypeDef::Set(_) | ScSpecTypeDef::Map(_) => {
+                Input::Rows(vec![])
+            }
+            ScSpecTypeDef::Tuple(t) => {
+                let ScSpecTypeTuple { value_types } = t.as_ref();
+                Input::Fields(value_types.iter().map(|t| Input::new(t, entries)).collect())
+            }
+            ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => {
+                match udt(entries, &name.to_string_lossy()) {
+                    Some(Udt::Struct(s)) => Input::Fields(
+                        s.fields
+                            .iter()
+                            .map(|f| Input::new(&f.type_, entries))
+                            .collect(),
+                    ),
+                    Some(Udt::Union(u)) => match u.cases.first() {
+                        Some(c) => Input::case(c, entries),
+                        None => Input::Text(String::new()),
+                    },
+                    Some(Udt::Enum(e)) => Input::Text(
+                        e.cases
+                            .first()
+                            .map(|c| c.name.to_string_lossy())
+                            .unwrap_or_default(),
+                    ),
+                    None => Input::Text(String::new()),
+                }
+            }
+            _ => Input::Text(String::new()),
+        }
+    }
+
+    /// Returns the input for the case of a union.
+    pub fn case(c: &ScSpecUdtUnionCaseV0, entries: &[ScSpecEntry]) -> Self {
+        Input::Case(
+            c.name.to_string_lossy(),
+            c.type_.as_ref().map(|t| Box::new(Input::new(t, entries))),
+        )
+    }
+
+    /// Returns the input for a new row of a vec, set or map.
+    pub fn row(t: &ScSpecTypeDef, entries: &[ScSpecEntry]) -> Option<Self> {
+        match t {
+            ScSpecTypeDef::Vec(v) => {
+                let ScSpecTypeVec { element_type } = v.as_ref();
+                Some(Input::new(element_type, entries))
+            }
+            ScSpecTypeDef::Set(s) => {
+                let ScSpecTypeSet { element_type } = s.as_ref();
+                Some(Input::new(element_type, entries))
+            }
+            ScSpecTypeDef::Map(m) => {
+                let ScSpecTypeMap {
+                    key_type,
+                    value_type,
+                } = m.as_ref();
+                Some(Input::Fields(vec![
+                    Input::new(key_type, entries),
+                    Input::new(value_type, entries),
+                ]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the input nested at the path, where each step is the index of a
+    /// field or row, or 0 for the value of a case or option.
+    pub fn get_mut(&mut self, path: &[usize]) -> Option<&mut Input> {
+        let (first, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return Some(self),
+        };
+        match self {
+            Input::Fields(inputs) | Input::Rows(inputs) => inputs.get_mut(*first)?.get_mut(rest),
+            Input::Case(_, Some(input)) | Input::Option(Some(input)) if *first == 0 => {
+                input.get_mut(rest)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the JSON for the input, in the form accepted by
+    /// [`super::encode::from_json`] for the type.
+    pub fn to_json(&self, t: &ScSpecTypeDef, entries: &[ScSpecEntry]) -> Result<Value, String> {
+        let unexpected = || format!("unexpected input for {}", type_name(t));
+        match (self, t) {
+            (Input::Text(s), ScSpecTypeDef::Val) => {
+                serde_json::from_str(s).map_err(|e| format!("invalid json: {e}"))
+            }
+            (Input::Text(s), _) => Ok(Value::String(s.trim().to_string())),
+            (Input::Option(None), ScSpecTypeDef::Option(_)) => Ok(Value::Null),
+            (Input::Option(Some(input)), ScSpecTypeDef::Option(o)) => {
+                let ScSpecTypeOption { value_type } = o.as_ref();
+                input.to_json(value_type, entries)
+            }
+            (Input::Rows(rows), ScSpecTypeDef::Vec(v)) => {
+                let ScSpecTypeVec { element_type } = v.as_ref();
+                array(rows.iter().map(|r| r.to_json(element_type, entries)))
+            }
+            (Input::Rows(rows), ScSpecTypeDef::Set(s)) => {
+                let ScSpecTypeSet { element_type } = s.as_ref();
+                array(rows.iter().map(|r| r.to_json(element_type, entries)))
+            }
+            (Input::Rows(rows), ScSpecTypeDef::Map(m)) => {
+                let ScSpecTypeMap {
+                    key_type,
+                    value_type,
+                } = m.as_ref();
+                array(rows.iter().map(|r| match r {
+                    Input::Fields(kv) => match kv.as_slice() {
+                        [k, v] => {
+                            array([k.to_json(key_type, entries), v.to_json(value_type, entries)])
+                        }
+                        _ => Err(unexpected()),
+                    },
+                    _ => Err(unexpected()),
+                }))
+            }
+            (Input::Fields(inputs), ScSpecTypeDef::Tuple(tuple)) => {
+                let ScSpecTypeTuple { value_types } = tuple.as_ref();
+                array(
+                    inputs
+                        .iter()
+                        .zip(value_types.iter())
+                        .map(|(i, t)| i.to_json(t, entries)),
+                )
+            }
+            (input, ScSpecTypeDef::Udt(ScSpecTypeUdt { name })) => {
+                match (input, udt(entries, &name.to_string_lossy())) {
+                    (Input::Fields(inputs), Some(Udt::Struct(s))) => {
+                        let tuple = s
+                            .fields
+                            .iter()
+                            .all(|f| f.name.to_string_lossy().parse::<u32>().is_ok());
+                        let values = inputs
+                            .iter()
+                            .zip(s.fields.iter())
+                            .map(|(i, f)| i.to_json(&f.type_, entries));
+                        if tuple {
+                            array(values)
+                        } else {
+                            Ok(Value::Object(
+                                s.fields
+                                    .iter()
+                                    .map(|f| f.name.to_string_lossy())
+                                    .zip(values)
+                                    .map(|(name, v)| Ok((name, v?)))
+                                    .collect::<Result<Map<_, _>, String>>()?,
+                            ))
+                        }
+                    }
+                    (Input::Case(name, value), Some(Udt::Union(u))) => {
+                        let case = u
+                            .cases
+                            .iter()
+                            .find(|c| &c.name.to_string_lossy() == name)
+                            .ok_or_else(|| format!("unknown case {name}"))?;
+                        match (value, &case.type_) {
+                            (Some(value), Some(t)) => {
+                                let mut o = Map::new();
+                                o.insert(name.clone(), value.to_json(t, entries)?);
+                                Ok(Value::Object(o))
+                            }
+                            _ => Ok(Value::String(name.clone())),
+                        }
+                    }
+                    _ => Err(unexpected()),
+                }
+            }
+            _ => Err(unexpected()),
+        }
+    }
+}
+
+fn array(values: impl IntoIterator<Item = Result<Value, String>>) -> Result<Value, String> {
+    Ok(Value::Array(values.into_iter().collect::<Result<_, _>>()?))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn text(s: &str) -> Input {
+        Input::Text(s.to_string())
+    }
+
+    /// Returns the type `(Option<u32>, Vec<bool>)`.
+    fn tuple() -> ScSpecTypeDef {
+        ScSpecTypeDef::Tuple(Box::new(ScSpecTypeTuple {
+            value_types: vec![
+                ScSpecTypeDef::Option(Box::new(ScSpecTypeOption {
+                    value_type: Box::new(ScSpecTypeDef::U32),
+                })),
+                ScSpecTypeDef::Vec(Box::new(ScSpecTypeVec {
+                    element_type: Box::new(ScSpecTypeDef::Bool),
+                })),
+            ]
+            .try_into()
+            .unwrap(),
+        }))
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            Input::new(&tuple(), &[]),
+            Input::Fields(vec![Input::Option(None), Input::Rows(vec![])])
+        );
+    }
+
+    #[test]
+    fn test_nested_to_json() {
+        let t = tuple();
+        let mut input = Input::new(&t, &[]);
+        assert_eq!(input.to_json(&t, &[]), Ok(json!([null, []])));
+        *input.get_mut(&[0]).unwrap() = Input::Option(Some(Box::new(text(" 7 "))));
+        *input.get_mut(&[1]).unwrap() = Input::Rows(vec![text("false"), text("false")]);
+        *input.get_mut(&[1, 1]).unwrap() = text("true");
+        assert_eq!(input.to_json(&t, &[]), Ok(json!(["7", ["false", "true"]])));
+        assert_eq!(input.get_mut(&[1, 2]), None);
+    }
+
+    #[test]
+    fn test_map_rows() {
+        let t = ScSpecTypeDef::Map(Box::new(ScSpecTypeMap {
+            key_type: Box::new(ScSpecTypeDef::Symbol),
+            value_type: Box::new(ScSpecTypeDef::U32),
+        }));
+        assert_eq!(
+            Input::row(&t, &[]),
+            Some(Input::Fields(vec![text(""), text("")]))
+        );
+        let input = Input::Rows(vec![Input::Fields(vec![text("k"), text("1")])]);
+        assert_eq!(input.to_json(&t, &[]), Ok(json!([["k", "1"]])));
+    }
+}
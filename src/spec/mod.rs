@@ -0,0 +1,84 @@
+pub mod decode;
+pub mod encode;
+pub mod input;
+
+use stellar_xdr::{
+    ScSpecEntry, ScSpecTypeDef, ScSpecTypeMap, ScSpecTypeOption, ScSpecTypeResult, ScSpecTypeSet,
+    ScSpecTypeTuple, ScSpecTypeUdt, ScSpecTypeVec, ScSpecUdtEnumV0, ScSpecUdtStructV0,
+    ScSpecUdtUnionV0,
+};
+
+pub enum Udt<'a> {
+    Struct(&'a ScSpecUdtStructV0),
+    Union(&'a ScSpecUdtUnionV0),
+    Enum(&'a ScSpecUdtEnumV0),
+}
+
+/// Returns the user defined type with the name from the spec entries.
+pub fn udt<'a>(entries: &'a [ScSpecEntry], name: &str) -> Option<Udt<'a>> {
+    entries.iter().find_map(|e| match e {
+        ScSpecEntry::UdtStructV0(s) if s.name.to_string_lossy() == name => Some(Udt::Struct(s)),
+        ScSpecEntry::UdtUnionV0(u) if u.name.to_string_lossy() == name => Some(Udt::Union(u)),
+        ScSpecEntry::UdtEnumV0(e) if e.name.to_string_lossy() == name => Some(Udt::Enum(e)),
+        _ => None,
+    })
+}
+
+/// Returns the name of the type as it would be written in Rust.
+pub fn type_name(t: &ScSpecTypeDef) -> String {
+    match t {
+        ScSpecTypeDef::Val => "RawVal".to_string(),
+        ScSpecTypeDef::U64 => "u64".to_string(),
+        ScSpecTypeDef::I64 => "i64".to_string(),
+        ScSpecTypeDef::U128 => "u128".to_string(),
+        ScSpecTypeDef::I128 => "i128".to_string(),
+        ScSpecTypeDef::U32 => "u32".to_string(),
+        ScSpecTypeDef::I32 => "i32".to_string(),
+        ScSpecTypeDef::Bool => "bool".to_string(),
+        ScSpecTypeDef::Symbol => "Symbol".to_string(),
+        ScSpecTypeDef::Bitset => "Bitset".to_string(),
+        ScSpecTypeDef::Status => "Status".to_string(),
+        ScSpecTypeDef::Bytes => "Bytes".to_string(),
+        ScSpecTypeDef::Invoker => "Invoker".to_string(),
+        ScSpecTypeDef::AccountId => "AccountId".to_string(),
+        ScSpecTypeDef::Option(o) => {
+            let ScSpecTypeOption { value_type } = o.as_ref();
+            format!("Option<{}>", type_name(value_type))
+        }
+        ScSpecTypeDef::Result(r) => {
+            let ScSpecTypeResult {
+                ok_type,
+                error_type,
+            } = r.as_ref();
+            format!("Result<{}, {}>", type_name(ok_type), type_name(error_type))
+        }
+        ScSpecTypeDef::Vec(v) => {
+            let ScSpecTypeVec { element_type } = v.as_ref();
+            format!("Vec<{}>", type_name(element_type))
+        }
+        ScSpecTypeDef::Map(m) => {
+            let ScSpecTypeMap {
+                key_type,
+                value_type,
+            } = m.as_ref();
+            format!("Map<{}, {}>", type_name(key_type), type_name(value_type))
+        }
+        ScSpecTypeDef::Set(s) => {
+            let ScSpecTypeSet { element_type } = s.as_ref();
+            format!("Set<{}>", type_name(element_type))
+        }
+        ScSpecTypeDef::Tuple(t) => {
+            let ScSpecTypeTuple { value_types } = t.as_ref();
+            format!(
+                "({})",
+                value_types
+                    .iter()
+                    .map(type_name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        ScSpecTypeDef::BytesN(b) => format!("BytesN<{}>", b.n),
+        ScSpecTypeDef::Udt(ScSpecTypeUdt { name }) => name.to_string_lossy(),
+    }
+}
@@ -1,12 +1,27 @@
-use std::time::Duration;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    time::Duration,
+};
 
 use super::horizonapi;
+use futures::{
+    channel::mpsc,
+    future::{select, Either},
+    stream, StreamExt, TryStreamExt,
+};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
 use soroban_spec::gen::rust::ToFormattedString;
 use stellar_xdr::{
-    ContractEvent, InvokeHostFunctionResult, LedgerFootprint, OperationResult, OperationResultTr,
-    ReadXdr, ScObject, ScSpecEntry, ScSpecFunctionV0, ScVal, TransactionMeta, TransactionMetaV3,
-    TransactionResult, TransactionResultResult,
+    Asset, ContractEvent, InvokeHostFunctionResult, LedgerEntryChange, LedgerFootprint,
+    OperationResult, OperationResultTr, ReadXdr, ScObject, ScSpecEntry, ScSpecFunctionV0, ScVal,
+    TransactionMeta, TransactionMetaV3, TransactionResult, TransactionResultResult,
 };
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{EventSource, MessageEvent};
 
 #[derive(Clone, PartialEq, PartialOrd)]
 pub struct Event {
@@ -20,25 +35,93 @@ impl Event {
     pub fn contract_id(&self) -> String {
         match &self.body {
             EventBody::Invocation(i) => i.id.clone(),
-            EventBody::Deployment(d) => d.id.clone(),
+            EventBody::Deployment(d) | EventBody::Ed25519Deployment { contract: d, .. } => {
+                d.id.clone()
+            }
+            EventBody::TokenDeployment { id } | EventBody::AssetTokenDeployment { id, .. } => {
+                id.clone()
+            }
         }
     }
 }
 
+/// An event for each of the host functions.
 #[derive(Clone, PartialEq, PartialOrd)]
 pub enum EventBody {
     Invocation(Invocation),
+    /// A deployment of WASM, with a contract id derived from the source account
+    /// of the transaction and a salt.
     Deployment(Contract),
+    /// A deployment of WASM, with a contract id derived from an ed25519 public
+    /// key and a salt.
+    Ed25519Deployment {
+        contract: Contract,
+        key: [u8; 32],
+    },
+    /// A deployment of the built-in token contract, with a contract id derived
+    /// from the source account of the transaction and a salt.
+    TokenDeployment {
+        id: String,
+    },
+    /// A deployment of the built-in token contract that wraps an asset.
+    AssetTokenDeployment {
+        id: String,
+        asset: Asset,
+    },
 }
 
-#[derive(Clone, PartialEq, PartialOrd)]
+impl EventBody {
+    /// Returns the contract deployed, if the event is a deployment of WASM.
+    pub fn deployed_contract(&self) -> Option<&Contract> {
+        match self {
+            EventBody::Deployment(c) | EventBody::Ed25519Deployment { contract: c, .. } => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn is_deployment(&self) -> bool {
+        !matches!(self, EventBody::Invocation(_))
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EventBody::Invocation(_) => "invoke",
+            EventBody::Deployment(_) => "deploy",
+            EventBody::Ed25519Deployment { .. } => "deploy (ed25519)",
+            EventBody::TokenDeployment { .. } => "deploy token",
+            EventBody::AssetTokenDeployment { .. } => "deploy asset token",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Invocation {
     pub id: String,
     pub function: String,
+    pub successful: bool,
+    /// The fee charged for the transaction of the invocation, in stroops.
+    pub fee_charged: Option<i64>,
+    /// The maximum fee the transaction of the invocation offered, in stroops.
+    pub max_fee: Option<i64>,
     pub args: Vec<Option<ScVal>>,
     pub result: Option<ScVal>,
+    /// The result of the transaction of the invocation, if it failed.
+    pub failure: Option<TransactionResultResult>,
     pub footprint: Option<LedgerFootprint>,
+    /// The events emitted by the invocation, if known. They are not known for
+    /// invocations in transactions with more than one operation.
     pub events: Option<Vec<ContractEvent>>,
+    /// The changes to ledger entries made by the invocation and its
+    /// transaction, as recorded in the meta of the transaction.
+    #[serde(default)]
+    pub changes: Option<Vec<LedgerEntryChange>>,
+    /// The account the invocation was submitted from, as a strkey.
+    pub source_account: Option<String>,
+    /// The sequence number of the ledger the invocation was included in.
+    pub ledger: Option<u32>,
+    /// The close time of the ledger the invocation was included in, in
+    /// seconds since the unix epoch.
+    pub close_time: Option<u64>,
 }
 
 #[derive(Clone, PartialEq, PartialOrd)]
@@ -52,32 +135,147 @@ impl Contract {
         sha256::digest(self.bytes.as_slice())
     }
 
+    /// Returns the spec of the contract, or an error if the WASM has no spec
+    /// that can be read.
+    pub fn spec(&self) -> Result<Vec<ScSpecEntry>, String> {
+        soroban_spec::read::from_wasm(&self.bytes).map_err(|e| e.to_string())
+    }
+
     pub fn fns(&self) -> Vec<String> {
-        soroban_spec::read::from_wasm(&self.bytes)
-            .unwrap()
+        self.fn_specs()
+            .into_iter()
+            .map(|f| f.name.to_string_lossy())
+            .collect::<Vec<_>>()
+    }
+
+    /// Returns the functions in the spec of the contract, which are none if the
+    /// spec cannot be read.
+    pub fn fn_specs(&self) -> Vec<ScSpecFunctionV0> {
+        self.spec()
+            .unwrap_or_default()
             .into_iter()
             .filter_map(|s| match s {
-                ScSpecEntry::FunctionV0(ScSpecFunctionV0 { name, .. }) => {
-                    Some(name.to_string_lossy())
-                }
+                ScSpecEntry::FunctionV0(f) => Some(f),
                 _ => None,
             })
             .collect::<Vec<_>>()
     }
 
-    pub fn spec_rust(&self) -> String {
-        soroban_spec::gen::rust::generate_from_wasm(self.bytes.as_slice(), "contract.wasm", None)
-            .unwrap()
+    pub fn fn_spec(&self, name: &str) -> Option<ScSpecFunctionV0> {
+        self.fn_specs()
+            .into_iter()
+            .find(|f| f.name.to_string_lossy() == name)
+    }
+
+    pub fn spec_rust(&self) -> Result<String, String> {
+        let file = soroban_spec::gen::rust::generate_from_wasm(
+            self.bytes.as_slice(),
+            "contract.wasm",
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(file
             .to_formatted_string()
-            .unwrap()
-            .replace("soroban_sdk::", "")
+            .map_err(|e| e.to_string())?
+            .replace("soroban_sdk::", ""))
+    }
+
+    pub fn spec_json(&self) -> Result<String, String> {
+        soroban_spec::gen::json::generate_from_wasm(self.bytes.as_slice())
+            .map_err(|e| e.to_string())
     }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The request failed to send or the response failed to be received.
+    Request(String),
+    /// The server responded with an unsuccessful status.
+    Status { url: String, status: u16 },
+    /// The server responded with 429 Too Many Requests.
+    RateLimited { retry_after: Option<Duration> },
+    /// The response was not the expected JSON.
+    Json(String),
+    /// A field of the response was not the expected XDR.
+    Xdr(String),
+    /// The stream of server-sent events failed to connect or disconnected.
+    Stream(String),
+}
+
+impl Error {
+    /// Returns true if the request that caused the error may succeed if it
+    /// is retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Request(_) | Error::RateLimited { .. } | Error::Stream(_) => true,
+            Error::Status { status, .. } => *status >= 500,
+            Error::Json(_) | Error::Xdr(_) => false,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Request(e) => write!(f, "request failed: {e}"),
+            Error::Status { url, status } => write!(f, "{url} responded with status {status}"),
+            Error::RateLimited {
+                retry_after: Some(d),
+            } => write!(f, "rate limited, retry after {}s", d.as_secs()),
+            Error::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            Error::Json(e) => write!(f, "invalid json: {e}"),
+            Error::Xdr(e) => write!(f, "invalid xdr: {e}"),
+            Error::Stream(e) => write!(f, "stream failed: {e}"),
+        }
+    }
+}
+
+/// Gets the URL and decodes the JSON response, retrying errors that are
+/// retryable.
+pub async fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, Error> {
+    let backoff = backoff::ExponentialBackoff {
+        max_elapsed_time: Some(Duration::from_secs(30)),
+        ..Default::default()
+    };
+    backoff::future::retry(backoff, || async {
+        let result = get_json_once(url).await;
+        result.map_err(|err| match err {
+            Error::RateLimited {
+                retry_after: Some(d),
+            } => backoff::Error::retry_after(err, d),
+            err if err.is_retryable() => backoff::Error::transient(err),
+            err => backoff::Error::permanent(err),
+        })
+    })
+    .await
+}
 
-    pub fn spec_json(&self) -> String {
-        soroban_spec::gen::json::generate_from_wasm(self.bytes.as_slice()).unwrap()
+async fn get_json_once<T: DeserializeOwned>(url: &str) -> Result<T, Error> {
+    let resp = reqwest::get(url)
+        .await
+        .map_err(|e| Error::Request(e.to_string()))?;
+    let status = resp.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs);
+        return Err(Error::RateLimited { retry_after });
     }
+    if !status.is_success() {
+        return Err(Error::Status {
+            url: url.to_string(),
+            status: status.as_u16(),
+        });
+    }
+    resp.json::<T>()
+        .await
+        .map_err(|e| Error::Json(e.to_string()))
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum Order {
     Asc,
     Desc,
@@ -92,216 +290,661 @@ impl Order {
     }
 }
 
-pub async fn latest_event_and_cursor(base_url: &str) -> (Option<Event>, Option<String>) {
-    let url = format!("{base_url}/operations?order=desc&limit=1");
-    let (events, cursor, _) = get_operations(base_url, &url).await;
-    (events.first().cloned(), cursor)
+pub async fn latest_event_and_cursor(
+    base_url: &str,
+) -> Result<(Option<Event>, Option<String>), Error> {
+    let url =
+        format!("{base_url}/operations?order=desc&limit=1&include_failed=true&join=transactions");
+    let (events, cursor, _) = get_operations(base_url, &url).await?;
+    Ok((events.first().cloned(), cursor))
 }
 
+/// Collects events continuously in the order from the cursor, calling f with
+/// every event, and s after each page with the error, if any, that occurred
+/// getting the page. Pages that error are retried after the delay.
 pub async fn collect_events(
     base_url: &str,
     cursor: &str,
     o: Order,
     d: Duration,
     f: impl Fn(Event),
+    s: impl Fn(Option<Error>),
 ) {
-    let mut next = get_operations_url(base_url, cursor, o, 10);
+    let mut next = get_operations_url(base_url, cursor, o, 10, true);
     loop {
-        let (events, _, next_url) = get_operations(base_url, &next).await;
-        for e in events {
-            f(e);
+        match get_operations(base_url, &next).await {
+            Ok((events, _, next_url)) => {
+                for e in events {
+                    f(e);
+                }
+                next = next_url;
+                s(None);
+            }
+            Err(err) => s(Some(err)),
+        }
+        gloo_timers::future::sleep(d).await;
+    }
+}
+
+/// The longest a stream can go without a message before it is assumed to have
+/// silently dropped, and is reconnected.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Streams events in ascending order from the cursor, using the server-sent
+/// events Horizon serves, calling f with every event, and s with the error, if
+/// any, that occurred getting events. Whenever the stream fails it is
+/// reconnected from the last paging token seen, and in the meantime a page is
+/// polled for, waiting the delay before reconnecting. If the browser does not
+/// support server-sent events this is the same as polling.
+pub async fn stream_events(
+    base_url: &str,
+    cursor: &str,
+    d: Duration,
+    f: impl Fn(Event),
+    s: impl Fn(Option<Error>),
+) {
+    let mut cursor = cursor.to_string();
+    loop {
+        if stream_events_once(base_url, &mut cursor, &f, &s)
+            .await
+            .is_ok()
+        {
+            // The stream went quiet, so reconnect straight away.
+            continue;
+        }
+        let url = get_operations_url(base_url, &cursor, Order::Asc, 10, true);
+        match get_operations(base_url, &url).await {
+            Ok((events, _, next_url)) => {
+                for e in events {
+                    f(e);
+                }
+                if let Some(next) = cursor_from_url(&next_url) {
+                    cursor = next;
+                }
+                s(None);
+            }
+            Err(err) => s(Some(err)),
         }
-        next = next_url;
         gloo_timers::future::sleep(d).await;
     }
 }
 
-pub fn get_operations_url(base_url: &str, cursor: &str, o: Order, limit: usize) -> String {
+/// An event source that is closed when dropped.
+struct EventStream {
+    source: EventSource,
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+    _onerror: Closure<dyn FnMut()>,
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
+
+/// Streams events from the cursor until the stream fails, or until it goes
+/// quiet for longer than [`STREAM_TIMEOUT`], in which case Ok is returned. The
+/// cursor is advanced past every record received.
+async fn stream_events_once(
+    base_url: &str,
+    cursor: &mut String,
+    f: &impl Fn(Event),
+    s: &impl Fn(Option<Error>),
+) -> Result<(), Error> {
+    let url = format!(
+        "{base_url}/operations?cursor={cursor}&order=asc&include_failed=true&join=transactions"
+    );
+    let source = EventSource::new(&url).map_err(|e| Error::Stream(format!("{e:?}")))?;
+    let (tx, mut rx) = mpsc::unbounded::<Option<String>>();
+    let tx_error = tx.clone();
+    let onmessage = Closure::<dyn FnMut(MessageEvent)>::new(move |m: MessageEvent| {
+        let _ = tx.unbounded_send(m.data().as_string());
+    });
+    let onerror = Closure::<dyn FnMut()>::new(move || {
+        let _ = tx_error.unbounded_send(None);
+    });
+    source.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    source.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    let _stream = EventStream {
+        source,
+        _onmessage: onmessage,
+        _onerror: onerror,
+    };
+    loop {
+        let timeout = gloo_timers::future::sleep(STREAM_TIMEOUT);
+        futures::pin_mut!(timeout);
+        let data = match select(rx.next(), timeout).await {
+            Either::Left((Some(Some(data)), _)) => data,
+            Either::Left(_) => return Err(Error::Stream("disconnected".to_string())),
+            Either::Right(_) => return Ok(()),
+        };
+        s(None);
+        // Messages that are not records, such as the hello Horizon sends when
+        // the stream opens, only show that the stream is alive.
+        let r = match serde_json::from_str::<horizonapi::operations::Record>(&data) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        for e in decode_records(base_url, [&r]).await? {
+            f(e);
+        }
+        *cursor = r.paging_token;
+    }
+}
+
+/// Returns the cursor in the query of the paging URL.
+fn cursor_from_url(url: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .find(|(k, _)| k == "cursor")
+        .map(|(_, v)| v.into_owned())
+}
+
+#[derive(Clone)]
+pub struct BackfillProgress {
+    pub pages: usize,
+    pub found: usize,
+    pub done: bool,
+    /// The error, if any, that occurred getting the last page. The page is
+    /// retried if the error is retryable, otherwise the backfill is done.
+    pub error: Option<Error>,
+}
+
+/// Walks the operation history backwards from the latest operation until the
+/// deployment of the contract is found, or the start of history is reached,
+/// calling f with every event of the contract, and p with the progress after
+/// each page.
+pub async fn backfill_contract(
+    base_url: &str,
+    contract_id: &str,
+    f: impl Fn(Event),
+    p: impl Fn(BackfillProgress),
+) {
+    let mut progress = BackfillProgress {
+        pages: 0,
+        found: 0,
+        done: false,
+        error: None,
+    };
+    let cursor = loop {
+        match latest_event_and_cursor(base_url).await {
+            Ok((_, cursor)) => break cursor,
+            Err(err) => {
+                let retryable = err.is_retryable();
+                progress.error = Some(err);
+                if !retryable {
+                    progress.done = true;
+                    p(progress);
+                    return;
+                }
+                p(progress.clone());
+                gloo_timers::future::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    };
+    if let Some(cursor) = cursor {
+        // The cursor is exclusive, so step one past the latest operation.
+        let cursor = cursor
+            .parse::<u64>()
+            .map(|c| (c + 1).to_string())
+            .unwrap_or(cursor);
+        // Most records are filtered out, so their transactions are not
+        // joined, unless a page has enough records that need them.
+        let mut next = get_operations_url(base_url, &cursor, Order::Desc, 200, false);
+        loop {
+            let page = get_operations_filtered(base_url, &next, |r| {
+                matches!(invoked_contract_id(r), Some(id) if id == contract_id) || is_deployment(r)
+            })
+            .await;
+            let (events, first, next_url) = match page {
+                Ok(page) => page,
+                Err(err) => {
+                    let retryable = err.is_retryable();
+                    progress.error = Some(err);
+                    if !retryable {
+                        break;
+                    }
+                    p(progress.clone());
+                    gloo_timers::future::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+            progress.error = None;
+            progress.pages += 1;
+            let mut deployed = false;
+            for e in events {
+                if e.contract_id() == contract_id {
+                    deployed |= e.body.is_deployment();
+                    progress.found += 1;
+                    f(e);
+                }
+            }
+            if deployed || first.is_none() {
+                break;
+            }
+            p(progress.clone());
+            next = next_url;
+        }
+    }
+    progress.done = true;
+    p(progress);
+}
+
+/// Returns the URL of a page of operations. If join is true the transaction of
+/// each operation is embedded in it, saving fetching them separately.
+pub fn get_operations_url(
+    base_url: &str,
+    cursor: &str,
+    o: Order,
+    limit: usize,
+    join: bool,
+) -> String {
     format!(
-        "{base_url}/operations?cursor={}&order={}&limit={}",
+        "{base_url}/operations?cursor={}&order={}&limit={}&include_failed=true{}",
         cursor,
         o.query_param_value(),
         limit,
+        if join { "&join=transactions" } else { "" },
     )
 }
 
-pub async fn get_operations(base_url: &str, url: &str) -> (Vec<Event>, Option<String>, String) {
-    let backoff = backoff::ExponentialBackoff::default();
-    let resp = backoff::future::retry(backoff, || async {
-        let result = reqwest::get(url).await;
-        match result {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    match resp.json::<horizonapi::operations::Response>().await {
-                        Ok(resp) => Ok(resp),
-                        Err(_) => Err(backoff::Error::transient(())),
-                    }
-                } else {
-                    Err(backoff::Error::transient(()))
-                }
-            }
-            Err(_) => Err(backoff::Error::transient(())),
-        }
-    })
-    .await
-    .unwrap();
+pub async fn get_operations(
+    base_url: &str,
+    url: &str,
+) -> Result<(Vec<Event>, Option<String>, String), Error> {
+    get_operations_filtered(base_url, url, |_| true).await
+}
+
+/// Same as [`get_operations`], but only decodes the records accepted by the
+/// filter, which avoids fetching the transactions of the records it rejects.
+pub async fn get_operations_filtered(
+    base_url: &str,
+    url: &str,
+    filter: impl Fn(&horizonapi::operations::Record) -> bool,
+) -> Result<(Vec<Event>, Option<String>, String), Error> {
+    let mut resp = get_json::<horizonapi::operations::Response>(url).await?;
+    let next = resp.links.next.href.clone();
 
-    let records = resp
+    // Fetching the page again with transactions joined is one request, so it
+    // is cheaper than fetching the transactions of the page one at a time when
+    // there is more than one of them. The next page is still fetched without
+    // them joined.
+    let missing = resp
         .embedded
         .records
         .iter()
-        .filter(|r| r.r#type == "invoke_host_function");
+        .filter(|r| filter(r) && needs_transaction(r) && r.transaction.is_none())
+        .map(|r| r.transaction_hash.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+    if missing > 1 {
+        resp = get_json::<horizonapi::operations::Response>(&format!("{url}&join=transactions"))
+            .await?;
+    }
+
+    let records = resp.embedded.records.iter().filter(|r| filter(r));
+    let events = decode_records(base_url, records).await?;
+    Ok((
+        events,
+        resp.embedded
+            .records
+            .first()
+            .map(|r| r.paging_token.clone()),
+        next,
+    ))
+}
+
+/// Returns the event of the operation, or None if the operation is not an
+/// invoke or deployment of a contract.
+pub async fn get_operation_event(base_url: &str, id: &str) -> Result<Option<Event>, Error> {
+    let url = format!("{base_url}/operations/{id}?join=transactions");
+    let r = get_json::<horizonapi::operations::Record>(&url).await?;
+    Ok(decode_records(base_url, [&r]).await?.pop())
+}
+
+/// Returns the events of the operations in the transaction.
+pub async fn get_transaction_events(base_url: &str, hash: &str) -> Result<Vec<Event>, Error> {
+    let url =
+        format!("{base_url}/transactions/{hash}/operations?include_failed=true&join=transactions");
+    let (events, _, _) = get_operations(base_url, &url).await?;
+    Ok(events)
+}
+
+/// The most transactions fetched at once when decoding records.
+const TX_CONCURRENCY: usize = 5;
+
+/// The most transactions kept in the cache before it is cleared.
+const TX_CACHE_SIZE: usize = 500;
+
+thread_local! {
+    /// Transactions fetched, by URL. Transactions never change once they are
+    /// in a ledger, so they never need fetching again.
+    static TX_CACHE: RefCell<HashMap<String, horizonapi::transaction::Response>> =
+        RefCell::default();
+}
+
+/// Decodes the records into events, skipping records that are not an invoke
+/// or deployment of a contract. The transactions of the records are used if
+/// they were joined, and are otherwise fetched concurrently, each only once.
+async fn decode_records<'a>(
+    base_url: &str,
+    records: impl IntoIterator<Item = &'a horizonapi::operations::Record>,
+) -> Result<Vec<Event>, Error> {
+    let records = records
+        .into_iter()
+        .filter(|r| r.r#type == "invoke_host_function")
+        .collect::<Vec<_>>();
+    let mut txs = HashMap::new();
+    for r in &records {
+        if let Some(tx) = &r.transaction {
+            txs.insert(r.transaction_hash.as_str(), tx.clone());
+        }
+    }
+    let mut seen = HashSet::new();
+    let missing = records
+        .iter()
+        .filter(|r| needs_transaction(r) && !txs.contains_key(r.transaction_hash.as_str()))
+        .map(|r| r.transaction_hash.as_str())
+        .filter(|hash| seen.insert(*hash))
+        .collect::<Vec<_>>();
+    let fetched = stream::iter(missing)
+        .map(|hash| async move { Ok::<_, Error>((hash, get_transaction(base_url, hash).await?)) })
+        .buffered(TX_CONCURRENCY)
+        .try_collect::<Vec<_>>()
+        .await?;
+    txs.extend(fetched);
 
-    let mut events: Vec<Event> = vec![];
+    let mut events = vec![];
     for r in records {
-        match r.function.as_deref() {
-            Some("HostFunctionHostFnInvokeContract") => {
-                let id = if let Some(id) = r.parameters.get(0) {
-                    if let Ok(ScVal::Object(Some(ScObject::Bytes(id)))) =
-                        ScVal::from_xdr_base64(&id.value)
-                    {
-                        Some(hex::encode(id))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-                let function = if let Some(function) = r.parameters.get(1) {
-                    if let Ok(ScVal::Symbol(function)) = ScVal::from_xdr_base64(&function.value) {
-                        Some(function.to_string_lossy())
-                    } else {
-                        None
-                    }
+        if let Some(tx) = txs.get(r.transaction_hash.as_str()) {
+            if let Some(e) = decode_record(r, tx)? {
+                events.push(e);
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// The host functions that deploy a contract, whose contract id is only known
+/// from the result of their transaction.
+const DEPLOYMENT_FUNCTIONS: &[&str] = &[
+    "HostFunctionHostFnCreateContractWithEd25519",
+    "HostFunctionHostFnCreateContractWithSourceAccount",
+    "HostFunctionHostFnCreateTokenContractWithSourceAccount",
+    "HostFunctionHostFnCreateTokenContractWithAsset",
+];
+
+/// Returns true if the record is a deployment of a contract.
+fn is_deployment(r: &horizonapi::operations::Record) -> bool {
+    matches!(r.function.as_deref(), Some(f) if DEPLOYMENT_FUNCTIONS.contains(&f))
+}
+
+/// Returns true if the transaction of the record is needed to decode it.
+fn needs_transaction(r: &horizonapi::operations::Record) -> bool {
+    r.function.as_deref() == Some("HostFunctionHostFnInvokeContract") || is_deployment(r)
+}
+
+/// Decodes the operation record into an event, using its transaction for the
+/// result. Returns None for records that are not an invoke or deployment of a
+/// contract. Parts of the transaction that cannot be decoded are left out of
+/// the event, rather than failing the page the record is on.
+fn decode_record(
+    r: &horizonapi::operations::Record,
+    tx: &horizonapi::transaction::Response,
+) -> Result<Option<Event>, Error> {
+    // Without the index of the operation its result is not known.
+    let index = operation_index(&r.id);
+    match r.function.as_deref() {
+        Some("HostFunctionHostFnInvokeContract") => {
+            let id = invoked_contract_id(r);
+            let function = if let Some(function) = r.parameters.get(1) {
+                if let Ok(ScVal::Symbol(function)) = ScVal::from_xdr_base64(&function.value) {
+                    Some(function.to_string_lossy())
                 } else {
                     None
-                };
-                let args = r
-                    .parameters
-                    .iter()
-                    .skip(2)
-                    .map(|a| ScVal::from_xdr_base64(&a.value).ok())
-                    .collect::<Vec<_>>();
-                let tx = get_transaction(base_url, &r.transaction_hash).await;
-                let result = if let Ok(TransactionResult {
+                }
+            } else {
+                None
+            };
+            let args = r
+                .parameters
+                .iter()
+                .skip(2)
+                .map(|a| ScVal::from_xdr_base64(&a.value).ok())
+                .collect::<Vec<_>>();
+            let fee_charged = tx.fee_charged.parse().ok();
+            let max_fee = tx.max_fee.parse().ok();
+            let (result, failure) = match decode_xdr("result_xdr", tx.result_xdr.clone()) {
+                Ok(TransactionResult {
                     result: TransactionResultResult::TxSuccess(op_results),
                     ..
-                }) = TransactionResult::from_xdr_base64(tx.result_xdr)
-                {
+                }) => {
                     if let Some(OperationResult::OpInner(OperationResultTr::InvokeHostFunction(
                         InvokeHostFunctionResult::Success(result),
-                    ))) = op_results.get(0)
+                    ))) = index.and_then(|i| op_results.get(i))
                     {
-                        Some(result.clone())
+                        (Some(result.clone()), None)
                     } else {
-                        None
+                        (None, None)
                     }
-                } else {
-                    None
-                };
-                let contract_events =
-                    if let Ok(TransactionMeta::V3(TransactionMetaV3 { events, .. })) =
-                        TransactionMeta::from_xdr_base64(tx.result_meta_xdr)
-                    {
-                        Some(events.into())
-                    } else {
-                        None
-                    };
-                let footprint = if let Some(footprint) = &r.footprint {
-                    if let Ok(footprint) = LedgerFootprint::from_xdr_base64(footprint) {
-                        Some(footprint)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-                if let (Some(id), Some(function)) = (id, function) {
-                    events.push(Event {
-                        id: r.id.clone(),
-                        tx: r.transaction_hash.clone(),
-                        at: r.created_at.clone(),
-                        body: EventBody::Invocation(Invocation {
-                            id,
-                            function,
-                            args,
-                            result,
-                            footprint,
-                            events: contract_events,
-                        }),
-                    });
                 }
-            }
-            Some("HostFunctionHostFnCreateContractWithSourceAccount") => {
-                let tx = get_transaction(base_url, &r.transaction_hash).await;
-                let id = if let Ok(TransactionResult {
-                    result: TransactionResultResult::TxSuccess(op_results),
-                    ..
-                }) = TransactionResult::from_xdr_base64(tx.result_xdr)
-                {
-                    if let Some(OperationResult::OpInner(OperationResultTr::InvokeHostFunction(
-                        InvokeHostFunctionResult::Success(ScVal::Object(Some(ScObject::Bytes(id)))),
-                    ))) = op_results.get(0)
-                    {
-                        Some(hex::encode(id))
-                    } else {
-                        None
+                Ok(TransactionResult { result, .. }) => (None, Some(result)),
+                Err(_) => (None, None),
+            };
+            let (contract_events, changes) =
+                match decode_xdr("result_meta_xdr", tx.result_meta_xdr.clone()) {
+                    Ok(TransactionMeta::V3(TransactionMetaV3 {
+                        tx_changes_before,
+                        operations,
+                        tx_changes_after,
+                        events,
+                        ..
+                    })) => {
+                        let changes = index.and_then(|i| operations.get(i)).map(|o| {
+                            tx_changes_before
+                                .0
+                                .iter()
+                                .chain(o.changes.0.iter())
+                                .chain(tx_changes_after.0.iter())
+                                .cloned()
+                                .collect()
+                        });
+                        // The meta does not record which operation emitted
+                        // each event, so the events are only known to be the
+                        // invocation's if it is the only operation.
+                        let events = (tx.operation_count == 1).then(|| events.into());
+                        (events, changes)
                     }
-                } else {
-                    None
+                    _ => (None, None),
                 };
-                let bytes = if let Some(code) = r.parameters.get(0) {
-                    if let Ok(ScVal::Object(Some(ScObject::Bytes(bytes)))) =
-                        ScVal::from_xdr_base64(&code.value)
-                    {
-                        Some(bytes.into())
-                    } else {
-                        None
-                    }
+            let footprint = if let Some(footprint) = &r.footprint {
+                if let Ok(footprint) = LedgerFootprint::from_xdr_base64(footprint) {
+                    Some(footprint)
                 } else {
                     None
-                };
-                if let (Some(id), Some(bytes)) = (id, bytes) {
-                    events.push(Event {
-                        id: r.id.clone(),
-                        tx: r.transaction_hash.clone(),
-                        at: r.created_at.clone(),
-                        body: EventBody::Deployment(Contract { id, bytes }),
-                    });
                 }
+            } else {
+                None
+            };
+            if let (Some(id), Some(function)) = (id, function) {
+                return Ok(Some(Event {
+                    id: r.id.clone(),
+                    tx: r.transaction_hash.clone(),
+                    at: r.created_at.clone(),
+                    body: EventBody::Invocation(Invocation {
+                        id,
+                        function,
+                        successful: r.transaction_successful,
+                        fee_charged,
+                        max_fee,
+                        args,
+                        result,
+                        failure,
+                        footprint,
+                        events: contract_events,
+                        changes,
+                        source_account: Some(r.source_account.clone()),
+                        ledger: tx.ledger.try_into().ok(),
+                        close_time: unix_time(&tx.created_at),
+                    }),
+                }));
+            }
+        }
+        Some(f) if DEPLOYMENT_FUNCTIONS.contains(&f) => {
+            if let Some(body) = deployment_body(r, tx, index, f) {
+                return Ok(Some(Event {
+                    id: r.id.clone(),
+                    tx: r.transaction_hash.clone(),
+                    at: r.created_at.clone(),
+                    body,
+                }));
             }
-            _ => {}
         }
+        _ => {}
     }
-    (
-        events,
-        resp.embedded
-            .records
-            .first()
-            .map(|r| r.paging_token.clone()),
-        resp.links.next.href,
-    )
+    Ok(None)
 }
 
-pub async fn get_transaction(base_url: &str, hash: &str) -> horizonapi::transaction::Response {
-    let url = format!("{base_url}/transactions/{hash}");
-    let backoff = backoff::ExponentialBackoff::default();
-    backoff::future::retry(backoff, || async {
-        let result = reqwest::get(&url).await;
-        match result {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    match resp.json::<horizonapi::transaction::Response>().await {
-                        Ok(resp) => Ok(resp),
-                        Err(_) => Err(backoff::Error::transient(())),
-                    }
-                } else {
-                    Err(backoff::Error::transient(()))
-                }
-            }
-            Err(_) => Err(backoff::Error::transient(())),
+/// Returns the body of the event for the deployment in the record, or None if
+/// the contract id or a parameter cannot be decoded.
+fn deployment_body(
+    r: &horizonapi::operations::Record,
+    tx: &horizonapi::transaction::Response,
+    index: Option<usize>,
+    f: &str,
+) -> Option<EventBody> {
+    let id = deployed_contract_id(tx, index)?;
+    Some(match f {
+        "HostFunctionHostFnCreateContractWithSourceAccount" => EventBody::Deployment(Contract {
+            id,
+            bytes: parameter_bytes(r, 0)?,
+        }),
+        "HostFunctionHostFnCreateContractWithEd25519" => EventBody::Ed25519Deployment {
+            contract: Contract {
+                id,
+                bytes: parameter_bytes(r, 0)?,
+            },
+            key: parameter_bytes(r, 2)?.try_into().ok()?,
+        },
+        "HostFunctionHostFnCreateTokenContractWithSourceAccount" => {
+            EventBody::TokenDeployment { id }
         }
+        _ => EventBody::AssetTokenDeployment {
+            id,
+            asset: Asset::from_xdr(parameter_bytes(r, 0)?).ok()?,
+        },
     })
-    .await
-    .unwrap()
+}
+
+/// Returns the id of the contract deployed by the operation at the index, from
+/// the result of its transaction.
+fn deployed_contract_id(
+    tx: &horizonapi::transaction::Response,
+    index: Option<usize>,
+) -> Option<String> {
+    match decode_xdr("result_xdr", tx.result_xdr.clone()).ok()? {
+        TransactionResult {
+            result: TransactionResultResult::TxSuccess(op_results),
+            ..
+        } => match op_results.get(index?)? {
+            OperationResult::OpInner(OperationResultTr::InvokeHostFunction(
+                InvokeHostFunctionResult::Success(ScVal::Object(Some(ScObject::Bytes(id)))),
+            )) => Some(hex::encode(id)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the parameter of the record at the index, if it is bytes.
+fn parameter_bytes(r: &horizonapi::operations::Record, index: usize) -> Option<Vec<u8>> {
+    match ScVal::from_xdr_base64(&r.parameters.get(index)?.value).ok()? {
+        ScVal::Object(Some(ScObject::Bytes(bytes))) => Some(bytes.into()),
+        _ => None,
+    }
+}
+
+/// Returns the time in seconds since the unix epoch of the ISO 8601 time.
+fn unix_time(time: &str) -> Option<u64> {
+    let ms = js_sys::Date::parse(time);
+    (ms.is_finite() && ms >= 0.0).then(|| (ms / 1000.0) as u64)
+}
+
+/// Returns the index of the operation in its transaction, from the operation's
+/// id, which is a TOID holding the ledger, the transaction's order in the
+/// ledger, and the operation's order in the transaction starting at 1.
+pub fn operation_index(id: &str) -> Option<usize> {
+    let toid = id.parse::<u64>().ok()?;
+    ((toid & 0xFFF) as usize).checked_sub(1)
+}
+
+/// Decodes the base64 XDR of a field of a response.
+pub fn decode_xdr<T: ReadXdr>(field: &str, b64: String) -> Result<T, Error> {
+    T::from_xdr_base64(b64).map_err(|e| Error::Xdr(format!("{field}: {e}")))
+}
+
+/// Returns the id of the contract invoked by the record, if the record is an
+/// invoke of a contract. Deployments are not included because their contract
+/// id is only known from the result of their transaction.
+pub fn invoked_contract_id(r: &horizonapi::operations::Record) -> Option<String> {
+    if r.function.as_deref() != Some("HostFunctionHostFnInvokeContract") {
+        return None;
+    }
+    if let Some(id) = r.parameters.get(0) {
+        if let Ok(ScVal::Object(Some(ScObject::Bytes(id)))) = ScVal::from_xdr_base64(&id.value) {
+            return Some(hex::encode(id));
+        }
+    }
+    None
+}
+
+/// Gets the transaction, from the cache if it has been fetched before.
+pub async fn get_transaction(
+    base_url: &str,
+    hash: &str,
+) -> Result<horizonapi::transaction::Response, Error> {
+    let url = format!("{base_url}/transactions/{hash}");
+    if let Some(tx) = TX_CACHE.with(|c| c.borrow().get(&url).cloned()) {
+        return Ok(tx);
+    }
+    let tx = get_json::<horizonapi::transaction::Response>(&url).await?;
+    TX_CACHE.with(|c| {
+        let mut c = c.borrow_mut();
+        if c.len() >= TX_CACHE_SIZE {
+            c.clear();
+        }
+        c.insert(url, tx.clone());
+    });
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns the TOID of the operation, as Horizon formats it in ids.
+    fn toid(ledger: u64, tx: u64, op: u64) -> String {
+        (ledger << 32 | tx << 12 | op).to_string()
+    }
+
+    #[test]
+    fn test_operation_index_first() {
+        assert_eq!(operation_index(&toid(1234, 5, 1)), Some(0));
+    }
+
+    #[test]
+    fn test_operation_index_later() {
+        assert_eq!(operation_index(&toid(1234, 5, 3)), Some(2));
+        assert_eq!(operation_index(&toid(u32::MAX as u64, 1, 100)), Some(99));
+    }
+
+    #[test]
+    fn test_operation_index_unparsable() {
+        assert_eq!(operation_index(""), None);
+        assert_eq!(operation_index("not a toid"), None);
+        assert_eq!(operation_index("-1"), None);
+        // An id with no operation part is the id of a transaction.
+        assert_eq!(operation_index(&toid(1234, 5, 0)), None);
+    }
 }
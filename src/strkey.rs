@@ -0,0 +1,121 @@
+//! Encoding and decoding of Stellar strkeys for ed25519 public keys, the
+//! `G...` form of account ids.
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const VERSION_PUBLIC_KEY_ED25519: u8 = 6 << 3;
+
+pub fn encode_public_key(key: &[u8; 32]) -> String {
+    let mut data = vec![VERSION_PUBLIC_KEY_ED25519];
+    data.extend_from_slice(key);
+    let crc = crc16(&data);
+    data.extend_from_slice(&crc.to_le_bytes());
+    base32_encode(&data)
+}
+
+pub fn decode_public_key(s: &str) -> Result<[u8; 32], String> {
+    let data = base32_decode(s).ok_or_else(|| format!("invalid strkey: {s}"))?;
+    if data.len() != 35 || data[0] != VERSION_PUBLIC_KEY_ED25519 {
+        return Err(format!("not an account id strkey: {s}"));
+    }
+    let (payload, checksum) = data.split_at(33);
+    if crc16(payload).to_le_bytes() != checksum {
+        return Err(format!("invalid strkey checksum: {s}"));
+    }
+    Ok(payload[1..].try_into().unwrap())
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for b in data {
+        buffer = (buffer << 8) | u32::from(*b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = vec![];
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let v = ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = (buffer << 5) | v;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// CRC16-XModem, as used for strkey checksums.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for b in data {
+        crc ^= u16::from(*b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_zero_key() {
+        assert_eq!(
+            encode_public_key(&[0; 32]),
+            "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for key in [
+            [0; 32],
+            [1; 32],
+            [255; 32],
+            core::array::from_fn(|i| i as u8),
+        ] {
+            let s = encode_public_key(&key);
+            assert_eq!(s.len(), 56);
+            assert!(s.starts_with('G'));
+            assert_eq!(decode_public_key(&s), Ok(key));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_checksum() {
+        let mut s = encode_public_key(&[7; 32]);
+        s.replace_range(1..2, if &s[1..2] == "A" { "B" } else { "A" });
+        assert!(decode_public_key(&s).is_err());
+    }
+
+    #[test]
+    fn test_decode_not_public_key() {
+        // A seed strkey has a different version byte.
+        let mut data = vec![18 << 3];
+        data.extend_from_slice(&[0; 32]);
+        let crc = crc16(&data);
+        data.extend_from_slice(&crc.to_le_bytes());
+        assert!(decode_public_key(&base32_encode(&data)).is_err());
+        assert!(decode_public_key("not a strkey").is_err());
+    }
+}
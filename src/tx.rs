@@ -0,0 +1,58 @@
+use stellar_xdr::{
+    HostFunction, InvokeHostFunctionOp, LedgerFootprint, Memo, MuxedAccount, Operation,
+    OperationBody, Preconditions, ScObject, ScVal, SequenceNumber, Transaction,
+    TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256, WriteXdr,
+};
+
+/// The parts of a transaction that are not determined by a simulation.
+pub struct TxParams {
+    pub source_account: [u8; 32],
+    pub seq_num: i64,
+    pub fee: u32,
+}
+
+/// Returns an unsigned transaction envelope as base64 XDR, containing a
+/// single operation that invokes the function on the contract with the args,
+/// limited to the footprint.
+pub fn invoke_envelope(
+    params: &TxParams,
+    contract_id: &str,
+    function: &str,
+    args: &[ScVal],
+    footprint: &LedgerFootprint,
+) -> Result<String, String> {
+    let id = hex::decode(contract_id).map_err(|e| format!("contract id: {e}"))?;
+    let parameters = [
+        vec![
+            ScVal::Object(Some(ScObject::Bytes(
+                id.try_into().map_err(|_| "contract id too long")?,
+            ))),
+            ScVal::Symbol(function.try_into().map_err(|_| "function name too long")?),
+        ],
+        args.to_vec(),
+    ]
+    .concat();
+    let op = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            function: HostFunction::InvokeContract,
+            parameters: parameters.try_into().map_err(|_| "too many args")?,
+            footprint: footprint.clone(),
+        }),
+    };
+    let tx = Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(params.source_account)),
+        fee: params.fee,
+        seq_num: SequenceNumber(params.seq_num),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![op].try_into().map_err(|_| "too many operations")?,
+        ext: TransactionExt::V0,
+    };
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: vec![].try_into().map_err(|_| "too many signatures")?,
+    })
+    .to_xdr_base64()
+    .map_err(|e| format!("encoding transaction: {e}"))
+}
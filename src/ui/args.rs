@@ -0,0 +1,363 @@
+use crate::spec::{encode::from_input, input::Input, type_name, udt, Udt};
+
+use stellar_xdr::{
+    ScSpecEntry, ScSpecFunctionInputV0, ScSpecFunctionV0, ScSpecTypeDef, ScSpecTypeOption,
+    ScSpecTypeTuple, ScSpecTypeUdt, ScVal,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::{
+    events,
+    html::Scope,
+    prelude::{html, Component, Context, Html},
+    Callback, Properties, TargetCast,
+};
+
+#[derive(Default)]
+pub struct ArgsComp {
+    function: Option<ScSpecFunctionV0>,
+    inputs: Vec<Input>,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ArgsCompProps {
+    pub function: ScSpecFunctionV0,
+    pub entries: Vec<ScSpecEntry>,
+    pub onchange: Callback<Result<Vec<ScVal>, String>>,
+}
+
+pub enum ArgsCompMsg {
+    /// Replaces the input at the path, which starts with the index of the arg
+    /// and continues as in [`Input::get_mut`].
+    Input { path: Vec<usize>, input: Input },
+}
+
+impl Component for ArgsComp {
+    type Message = ArgsCompMsg;
+    type Properties = ArgsCompProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let comp = Self {
+            function: Some(ctx.props().function.clone()),
+            inputs: default_inputs(ctx.props()),
+        };
+        comp.emit(ctx);
+        comp
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ArgsCompMsg::Input { path, input } => {
+                let target = path
+                    .split_first()
+                    .and_then(|(index, rest)| self.inputs.get_mut(*index)?.get_mut(rest));
+                if let Some(target) = target {
+                    *target = input;
+                }
+                self.emit(ctx);
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        // The parent's callback changes on every render, so only reset the
+        // inputs when the function changes.
+        if self.function.as_ref() == Some(&ctx.props().function) {
+            return false;
+        }
+        self.function = Some(ctx.props().function.clone());
+        self.inputs = default_inputs(ctx.props());
+        self.emit(ctx);
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let scope = ctx.link();
+        let props = ctx.props();
+        let entries = &props.entries;
+        html! {
+            <table class="args">
+            {
+                for props.function.inputs.iter().zip(self.inputs.iter()).enumerate().map(|(index, (ScSpecFunctionInputV0 { name, type_ }, input))| {
+                    let error = match from_input(input, type_, entries) {
+                        Err(e) if input != &Input::new(type_, entries) => e,
+                        _ => String::new(),
+                    };
+                    html! {
+                        <tr>
+                            <td><strong>{ name.to_string_lossy() }</strong>{ ": " }{ type_name(type_) }</td>
+                            <td>{ widget(scope, entries, vec![index], input, type_) }</td>
+                            <td class="error">{ error }</td>
+                        </tr>
+                    }
+                })
+            }
+            </table>
+        }
+    }
+}
+
+impl ArgsComp {
+    fn emit(&self, ctx: &Context<Self>) {
+        let props = ctx.props();
+        let args = props
+            .function
+            .inputs
+            .iter()
+            .zip(self.inputs.iter())
+            .map(|(i, input)| {
+                from_input(input, &i.type_, &props.entries)
+                    .map_err(|e| format!("{}: {e}", i.name.to_string_lossy()))
+            })
+            .collect();
+        props.onchange.emit(args);
+    }
+}
+
+fn default_inputs(props: &ArgsCompProps) -> Vec<Input> {
+    props
+        .function
+        .inputs
+        .iter()
+        .map(|i| Input::new(&i.type_, &props.entries))
+        .collect()
+}
+
+/// Returns the widget for entering the input at the path, nesting a widget for
+/// each field, case value, option value and row.
+fn widget(
+    scope: &Scope<ArgsComp>,
+    entries: &[ScSpecEntry],
+    path: Vec<usize>,
+    input: &Input,
+    t: &ScSpecTypeDef,
+) -> Html {
+    let at = |step: usize| [path.as_slice(), &[step][..]].concat();
+    match (input, t) {
+        (Input::Text(text), ScSpecTypeDef::Bool) => {
+            let onchange = scope.callback(move |e: events::Event| ArgsCompMsg::Input {
+                path: path.clone(),
+                input: Input::Text(e.target_unchecked_into::<HtmlSelectElement>().value()),
+            });
+            html! {
+                <select {onchange}>
+                    { for ["false", "true"].iter().map(|v| html! { <option value={*v} selected={text.as_str() == *v}>{ v }</option> }) }
+                </select>
+            }
+        }
+        (Input::Text(text), ScSpecTypeDef::Val) => {
+            let oninput = scope.callback(move |e: events::InputEvent| ArgsCompMsg::Input {
+                path: path.clone(),
+                input: Input::Text(e.target_unchecked_into::<HtmlTextAreaElement>().value()),
+            });
+            html! { <textarea {oninput} value={text.clone()} placeholder={placeholder(t)} /> }
+        }
+        (Input::Text(text), ScSpecTypeDef::Udt(ScSpecTypeUdt { name }))
+            if matches!(udt(entries, &name.to_string_lossy()), Some(Udt::Enum(_))) =>
+        {
+            let cases = match udt(entries, &name.to_string_lossy()) {
+                Some(Udt::Enum(e)) => e
+                    .cases
+                    .iter()
+                    .map(|c| c.name.to_string_lossy())
+                    .collect::<Vec<_>>(),
+                _ => vec![],
+            };
+            let onchange = scope.callback(move |e: events::Event| ArgsCompMsg::Input {
+                path: path.clone(),
+                input: Input::Text(e.target_unchecked_into::<HtmlSelectElement>().value()),
+            });
+            html! {
+                <select {onchange}>
+                    { for cases.iter().map(|c| html! { <option value={c.clone()} selected={text == c}>{ c }</option> }) }
+                </select>
+            }
+        }
+        (Input::Text(text), _) => {
+            let oninput = scope.callback(move |e: events::InputEvent| ArgsCompMsg::Input {
+                path: path.clone(),
+                input: Input::Text(e.target_unchecked_into::<HtmlInputElement>().value()),
+            });
+            html! { <input type="text" {oninput} value={text.clone()} placeholder={placeholder(t)} /> }
+        }
+        (Input::Option(value), ScSpecTypeDef::Option(o)) => {
+            let ScSpecTypeOption { value_type } = o.as_ref();
+            let some = Input::Option(Some(Box::new(Input::new(value_type, entries))));
+            let onchange = {
+                let path = path.clone();
+                scope.callback(move |e: events::Event| ArgsCompMsg::Input {
+                    path: path.clone(),
+                    input: if e.target_unchecked_into::<HtmlSelectElement>().value() == "some" {
+                        some.clone()
+                    } else {
+                        Input::Option(None)
+                    },
+                })
+            };
+            html! {
+                <>
+                    <select {onchange}>
+                        <option value="none" selected={value.is_none()}>{ "none" }</option>
+                        <option value="some" selected={value.is_some()}>{ "some" }</option>
+                    </select>
+                    {
+                        if let Some(value) = value {
+                            widget(scope, entries, at(0), value, value_type)
+                        } else {
+                            html!()
+                        }
+                    }
+                </>
+            }
+        }
+        (Input::Fields(inputs), ScSpecTypeDef::Tuple(tuple)) => {
+            let ScSpecTypeTuple { value_types } = tuple.as_ref();
+            let names = (0..value_types.len())
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>();
+            fields(scope, entries, &path, inputs, &names, value_types)
+        }
+        (input, ScSpecTypeDef::Udt(ScSpecTypeUdt { name })) => {
+            match (input, udt(entries, &name.to_string_lossy())) {
+                (Input::Fields(inputs), Some(Udt::Struct(s))) => {
+                    let names = s
+                        .fields
+                        .iter()
+                        .map(|f| f.name.to_string_lossy())
+                        .collect::<Vec<_>>();
+                    let types = s.fields.iter().map(|f| f.type_.clone()).collect::<Vec<_>>();
+                    fields(scope, entries, &path, inputs, &names, &types)
+                }
+                (Input::Case(case, value), Some(Udt::Union(u))) => {
+                    let cases = u.cases.to_vec();
+                    let case_type = cases
+                        .iter()
+                        .find(|c| &c.name.to_string_lossy() == case)
+                        .and_then(|c| c.type_.clone());
+                    let case_entries = entries.to_vec();
+                    let onchange = {
+                        let path = path.clone();
+                        let cases = cases.clone();
+                        scope.callback(move |e: events::Event| {
+                            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+                            ArgsCompMsg::Input {
+                                path: path.clone(),
+                                input: cases
+                                    .iter()
+                                    .find(|c| c.name.to_string_lossy() == name)
+                                    .map(|c| Input::case(c, &case_entries))
+                                    .unwrap_or(Input::Case(name, None)),
+                            }
+                        })
+                    };
+                    html! {
+                        <>
+                            <select {onchange}>
+                                {
+                                    for cases.iter().map(|c| {
+                                        let c = c.name.to_string_lossy();
+                                        html! { <option value={c.clone()} selected={case == &c}>{ c }</option> }
+                                    })
+                                }
+                            </select>
+                            {
+                                match (value, &case_type) {
+                                    (Some(value), Some(t)) => widget(scope, entries, at(0), value, t),
+                                    _ => html!(),
+                                }
+                            }
+                        </>
+                    }
+                }
+                _ => html!(),
+            }
+        }
+        (Input::Rows(rows), t) => {
+            let row = match Input::row(t, entries) {
+                Some(row) => row,
+                None => return html!(),
+            };
+            let onclick_add = {
+                let path = path.clone();
+                let mut added = rows.clone();
+                added.push(row);
+                scope.callback(move |_| ArgsCompMsg::Input {
+                    path: path.clone(),
+                    input: Input::Rows(added.clone()),
+                })
+            };
+            html! {
+                <table class="rows">
+                {
+                    for rows.iter().enumerate().map(|(i, r)| {
+                        let onclick_remove = {
+                            let path = path.clone();
+                            let mut removed = rows.clone();
+                            removed.remove(i);
+                            scope.callback(move |_| ArgsCompMsg::Input {
+                                path: path.clone(),
+                                input: Input::Rows(removed.clone()),
+                            })
+                        };
+                        let cells = match (r, t) {
+                            (Input::Fields(kv), ScSpecTypeDef::Map(m)) if kv.len() == 2 => html! {
+                                <>
+                                    <td>{ widget(scope, entries, [at(i), vec![0]].concat(), &kv[0], &m.key_type) }</td>
+                                    <td>{ widget(scope, entries, [at(i), vec![1]].concat(), &kv[1], &m.value_type) }</td>
+                                </>
+                            },
+                            (r, ScSpecTypeDef::Vec(v)) => html! {
+                                <td>{ widget(scope, entries, at(i), r, &v.element_type) }</td>
+                            },
+                            (r, ScSpecTypeDef::Set(s)) => html! {
+                                <td>{ widget(scope, entries, at(i), r, &s.element_type) }</td>
+                            },
+                            _ => html!(),
+                        };
+                        html! {
+                            <tr>{ cells }<td><button onclick={onclick_remove}>{ "remove" }</button></td></tr>
+                        }
+                    })
+                }
+                <tr><td><button onclick={onclick_add}>{ "add" }</button></td></tr>
+                </table>
+            }
+        }
+        _ => html!(),
+    }
+}
+
+/// Returns a table with a row for each named field of a struct or element of a
+/// tuple, each with its own widget.
+fn fields(
+    scope: &Scope<ArgsComp>,
+    entries: &[ScSpecEntry],
+    path: &[usize],
+    inputs: &[Input],
+    names: &[String],
+    types: &[ScSpecTypeDef],
+) -> Html {
+    html! {
+        <table class="fields">
+        {
+            for inputs.iter().zip(names.iter().zip(types.iter())).enumerate().map(|(i, (input, (name, t)))| {
+                html! {
+                    <tr>
+                        <td>{ name }{ ": " }{ type_name(t) }</td>
+                        <td>{ widget(scope, entries, [path, &[i][..]].concat(), input, t) }</td>
+                    </tr>
+                }
+            })
+        }
+        </table>
+    }
+}
+
+fn placeholder(t: &ScSpecTypeDef) -> String {
+    match t {
+        ScSpecTypeDef::Bytes | ScSpecTypeDef::BytesN(_) => "hex".to_string(),
+        ScSpecTypeDef::AccountId => "G... or hex".to_string(),
+        ScSpecTypeDef::Val => "json scval".to_string(),
+        _ => type_name(t),
+    }
+}
@@ -0,0 +1,184 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::network::Limits;
+use crate::vm::budget::BudgetReport;
+
+use web_sys::HtmlSelectElement;
+use yew::{
+    events,
+    prelude::{html, Component, Context, Html},
+    Properties, TargetCast,
+};
+
+#[derive(Default)]
+pub struct BudgetComp {
+    sort: Sort,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct BudgetCompProps {
+    pub report: BudgetReport,
+    /// The report of the previous simulation of the same function, if any.
+    pub previous: Option<BudgetReport>,
+    pub limits: Limits,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Sort {
+    CostType,
+    Inputs,
+    Change,
+}
+
+impl Sort {
+    pub fn all() -> &'static [Sort] {
+        &[Sort::CostType, Sort::Inputs, Sort::Change]
+    }
+}
+
+impl FromStr for Sort {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cost type" => Ok(Sort::CostType),
+            "inputs" => Ok(Sort::Inputs),
+            "change" => Ok(Sort::Change),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Sort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sort::CostType => write!(f, "cost type"),
+            Sort::Inputs => write!(f, "inputs"),
+            Sort::Change => write!(f, "change"),
+        }
+    }
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Sort::Inputs
+    }
+}
+
+pub enum BudgetCompMsg {
+    SelectSort { sort: Sort },
+}
+
+impl Component for BudgetComp {
+    type Message = BudgetCompMsg;
+    type Properties = BudgetCompProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            BudgetCompMsg::SelectSort { sort } => {
+                self.sort = sort;
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let scope = ctx.link();
+        let props = ctx.props();
+        let report = &props.report;
+        let previous = props.previous.as_ref();
+        let onchange = {
+            scope.callback(|e: events::Event| BudgetCompMsg::SelectSort {
+                sort: Sort::from_str(&e.target_unchecked_into::<HtmlSelectElement>().value())
+                    .unwrap_or_default(),
+            })
+        };
+        let mut rows = report
+            .inputs
+            .iter()
+            .map(|(t, i)| {
+                let prev = previous.and_then(|p| p.input(t));
+                (t, *i, prev, change(*i, prev))
+            })
+            .collect::<Vec<_>>();
+        match self.sort {
+            Sort::CostType => rows.sort_by(|a, b| a.0.cmp(b.0)),
+            Sort::Inputs => rows.sort_by(|a, b| b.1.cmp(&a.1)),
+            Sort::Change => rows.sort_by(|a, b| b.3.unwrap_or(0).cmp(&a.3.unwrap_or(0))),
+        }
+        let totals = [
+            (
+                "cpu insns",
+                report.cpu_insns,
+                previous.map(|p| p.cpu_insns),
+                props.limits.cpu_insns,
+            ),
+            (
+                "mem bytes",
+                report.mem_bytes,
+                previous.map(|p| p.mem_bytes),
+                props.limits.mem_bytes,
+            ),
+        ];
+        html! {
+            <div class="budget">
+                <table>
+                <tr><th>{ "resource" }</th><th>{ "used" }</th><th>{ "limit" }</th><th>{ "previous" }</th><th>{ "change" }</th></tr>
+                {
+                    for totals.iter().map(|(name, used, prev, limit)| html! {
+                        <tr>
+                            <td>{ name }</td>
+                            <td>{ used }</td>
+                            <td>{ format!("{:.2}% of {limit}", percent(*used, *limit)) }</td>
+                            <td>{ prev.map(|p| p.to_string()).unwrap_or_default() }</td>
+                            <td>{ change(*used, *prev).map(signed).unwrap_or_default() }</td>
+                        </tr>
+                    })
+                }
+                </table>
+                <br/>
+                { "sort by: " }
+                <select {onchange}>
+                    {
+                        for Sort::all().iter().map(|s| {
+                            html! { <option value={s.to_string()} selected={s == &self.sort}>{s}</option> }
+                        })
+                    }
+                </select>
+                <table>
+                <tr><th>{ "cost type" }</th><th>{ "inputs" }</th><th>{ "previous" }</th><th>{ "change" }</th></tr>
+                {
+                    for rows.iter().map(|(t, i, prev, change)| html! {
+                        <tr>
+                            <td>{ t }</td>
+                            <td>{ i }</td>
+                            <td>{ prev.map(|p| p.to_string()).unwrap_or_default() }</td>
+                            <td>{ change.map(signed).unwrap_or_default() }</td>
+                        </tr>
+                    })
+                }
+                </table>
+            </div>
+        }
+    }
+}
+
+fn change(current: u64, previous: Option<u64>) -> Option<i128> {
+    previous.map(|p| i128::from(current) - i128::from(p))
+}
+
+fn signed(change: i128) -> String {
+    format!("{change:+}")
+}
+
+fn percent(used: u64, limit: u64) -> f64 {
+    if limit == 0 {
+        0.0
+    } else {
+        used as f64 / limit as f64 * 100.0
+    }
+}
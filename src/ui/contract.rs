@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use crate::network::Network;
+use crate::route::Route;
+use crate::spec::decode;
+use crate::stream::{Contract, Event, EventBody};
+use crate::vm::convert::from_host;
+use crate::vm::invoke::Config;
+use crate::vm::replay::{replay, ReplayOutcome, ReplayState};
+
+use soroban_env_host::xdr::{
+    ContractDataEntry, LedgerEntry, LedgerEntryData, LedgerKey, LedgerKeyContractData, ScStatic,
+    ScVal,
+};
+use yew::{
+    prelude::{html, Component, Context, Html},
+    Properties,
+};
+
+/// The page of a contract, aggregating its invocations and the storage
+/// reconstructed by replaying them.
+#[derive(Default)]
+pub struct ContractComp {
+    /// The contract, events and deployments last replayed.
+    contract: Option<Contract>,
+    events: Vec<Event>,
+    deployments: Vec<Contract>,
+    storage: Vec<(String, String)>,
+    replayed: usize,
+    errored: usize,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ContractCompProps {
+    pub network: Network,
+    pub contract: Contract,
+    /// The events of the contract in the history loaded.
+    pub events: Vec<Event>,
+    pub deployments: Vec<Contract>,
+}
+
+pub enum ContractCompMsg {}
+
+impl Component for ContractComp {
+    type Message = ContractCompMsg;
+    type Properties = ContractCompProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut comp = Self::default();
+        comp.replay(ctx);
+        comp
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, _msg: Self::Message) -> bool {
+        false
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let props = ctx.props();
+        if self.contract.as_ref() != Some(&props.contract)
+            || self.events != props.events
+            || self.deployments != props.deployments
+        {
+            self.replay(ctx);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let mut events = props.events.iter().collect::<Vec<_>>();
+        events.sort_by(|a, b| b.id.cmp(&a.id));
+        let invocations = events
+            .iter()
+            .filter_map(|e| match &e.body {
+                EventBody::Invocation(i) => Some((e, i)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut counts = BTreeMap::<&str, (usize, usize)>::new();
+        for (_, i) in &invocations {
+            let (calls, failed) = counts.entry(i.function.as_str()).or_default();
+            *calls += 1;
+            if !i.successful {
+                *failed += 1;
+            }
+        }
+        let emitted = invocations
+            .iter()
+            .flat_map(|(e, i)| {
+                i.events
+                    .iter()
+                    .flatten()
+                    .map(move |c| format!("op: {}\n{}", e.id, decode::event(c)))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        html! {
+            <div class="component contract">
+                <strong>{ "invocations: " }</strong>{ invocations.len() }{ " (in the history loaded)" }
+                <br/>
+                <table>
+                <tr><th>{ "function" }</th><th>{ "calls" }</th><th>{ "failed" }</th></tr>
+                {
+                    for counts.iter().map(|(function, (calls, failed))| html! {
+                        <tr><td>{ function }</td><td>{ calls }</td><td>{ failed }</td></tr>
+                    })
+                }
+                </table>
+                <br/>
+                <strong>{ "timeline: " }</strong>
+                <br/>
+                <table>
+                <tr><th>{ "at" }</th><th>{ "op" }</th><th>{ "event" }</th><th>{ "function" }</th><th>{ "status" }</th></tr>
+                {
+                    for events.iter().map(|e| {
+                        let (event, function, status) = match &e.body {
+                            EventBody::Invocation(i) => (
+                                "invoke",
+                                i.function.clone(),
+                                if i.successful { "ok" } else { "failed" },
+                            ),
+                            body => (body.kind(), String::new(), "ok"),
+                        };
+                        html! {
+                            <tr>
+                                <td>{ &e.at }</td>
+                                <td><a href={ Route::Op(e.id.clone()).to_string() }>{ &e.id }</a></td>
+                                <td>{ event }</td>
+                                <td>{ function }</td>
+                                <td>{ status }</td>
+                            </tr>
+                        }
+                    })
+                }
+                </table>
+                <br/>
+                <strong>{ "events: " }</strong>
+                <br/>
+                <pre><code>{ emitted }</code></pre>
+                <br/>
+                <strong>{ "storage: " }</strong>
+                { format!(" (reconstructed by replaying the {} invocations in the history loaded, {} errored; entries written before it are missing)", self.replayed, self.errored) }
+                <br/>
+                <table>
+                <tr><th>{ "key" }</th><th>{ "value" }</th></tr>
+                {
+                    for self.storage.iter().map(|(k, v)| html! {
+                        <tr><td><code>{ k }</code></td><td><code>{ v }</code></td></tr>
+                    })
+                }
+                </table>
+            </div>
+        }
+    }
+}
+
+impl ContractComp {
+    /// Replays the invocations of the contract to reconstruct its storage.
+    /// Each invocation is replayed from the source account it had on chain, so
+    /// the source account of the config is only used where that is unknown.
+    fn replay(&mut self, ctx: &Context<Self>) {
+        let props = ctx.props();
+        self.contract = Some(props.contract.clone());
+        self.events = props.events.clone();
+        self.deployments = props.deployments.clone();
+        let config = Config {
+            network_passphrase: props.network.passphrase.clone(),
+            source_account: [0; 32],
+            sequence_number: 0,
+            timestamp: 0,
+            fee_rates: props.network.fee_rates.clone(),
+        };
+        let (storage, steps) = replay(&config, &props.contract, &props.events, &props.deployments);
+        self.replayed = steps
+            .iter()
+            .filter(|s| matches!(s.outcome, ReplayOutcome::Replayed { .. }))
+            .count();
+        self.errored = steps
+            .iter()
+            .filter(|s| matches!(s.outcome, ReplayOutcome::Errored { .. }))
+            .count();
+        self.storage = storage
+            .as_ref()
+            .map(ReplayState::entries)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(k, e)| contract_data(&props.contract.id, k, e))
+            .collect();
+    }
+}
+
+/// Returns the key and value of the entry decoded, if the entry is data of the
+/// contract other than its code.
+fn contract_data(id: &str, key: &LedgerKey, entry: &LedgerEntry) -> Option<(String, String)> {
+    let (contract_id, key, val) = match (key, entry) {
+        (
+            LedgerKey::ContractData(LedgerKeyContractData { contract_id, key }),
+            LedgerEntry {
+                data: LedgerEntryData::ContractData(ContractDataEntry { val, .. }),
+                ..
+            },
+        ) => (contract_id, key, val),
+        _ => return None,
+    };
+    if hex::encode(contract_id.0) != id || key == &ScVal::Static(ScStatic::LedgerKeyContractCode) {
+        return None;
+    }
+    Some((
+        decode::untyped(&from_host(key)?),
+        decode::untyped(&from_host(val)?),
+    ))
+}
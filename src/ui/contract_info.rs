@@ -109,11 +109,17 @@ impl Component for ContractInfoComp {
                 </select><br/>
                 {
                     match self.format {
-                        Format::Rust => html! {
-                            <pre><code class="language-rust">{ props.contract.spec_rust() }</code></pre>
+                        Format::Rust => match props.contract.spec_rust() {
+                            Ok(rust) => html! {
+                                <pre><code class="language-rust">{ rust }</code></pre>
+                            },
+                            Err(err) => html! { <div class="error">{ format!("reading spec failed: {err}") }</div> },
                         },
-                        Format::Json => html! {
-                            <pre><code class="language-json">{ props.contract.spec_json() }</code></pre>
+                        Format::Json => match props.contract.spec_json() {
+                            Ok(json) => html! {
+                                <pre><code class="language-json">{ json }</code></pre>
+                            },
+                            Err(err) => html! { <div class="error">{ format!("reading spec failed: {err}") }</div> },
                         },
                     }
                 }
@@ -1,6 +1,9 @@
 use crate::{
+    network::Network,
+    route::Route,
+    spec::decode,
     stream::{Event, EventBody},
-    HORIZON_BASE_URL,
+    strkey,
 };
 
 use yew::{
@@ -13,6 +16,7 @@ pub struct EventInfoComp {}
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct EventInfoCompProps {
+    pub network: Network,
     pub event: Event,
 }
 
@@ -33,16 +37,24 @@ impl Component for EventInfoComp {
     fn view(&self, ctx: &Context<Self>) -> Html {
         let props = ctx.props();
         let event = &props.event;
-        let tx_url = format!("{}/transactions/{}", HORIZON_BASE_URL, event.tx);
+        let tx_url = format!("{}/transactions/{}", props.network.horizon_url, event.tx);
         html! {
             <div class="component eventinfo">
                 <strong>{ "tx: " }</strong><a href={ tx_url } target="_blank">{ &event.tx }</a><br/>
+                <strong>{ "link: " }</strong><a href={ Route::Op(event.id.clone()).to_string() }>{ Route::Op(event.id.clone()).to_string() }</a>
+                { " " }<a href={ Route::Contract(event.contract_id()).to_string() }>{ "contract" }</a><br/>
                 <strong>{ "at: " }</strong>{ &event.at }<br/>
                 <strong>{ "event: " }</strong>
+                { event.body.kind() }
                 {
                     match &event.body {
-                        EventBody::Invocation(_) => "invoke",
-                        EventBody::Deployment(_) => "deploy",
+                        EventBody::Ed25519Deployment { key, .. } => html! {
+                            <><br/><strong>{ "key: " }</strong>{ strkey::encode_public_key(key) }</>
+                        },
+                        EventBody::AssetTokenDeployment { asset, .. } => html! {
+                            <><br/><strong>{ "asset: " }</strong>{ decode::asset(asset) }</>
+                        },
+                        _ => html!(),
                     }
                 }
             </div>
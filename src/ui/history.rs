@@ -1,8 +1,13 @@
-use std::time::Duration;
+use std::{cell::RefCell, time::Duration};
 
 use crate::{
-    stream::{collect_events, latest_event_and_cursor, Event, EventBody, Order},
-    HORIZON_BASE_URL,
+    cache::{Cache, Cursors},
+    network::Network,
+    route::Route,
+    stream::{
+        backfill_contract, collect_events, get_operation_event, get_transaction_events,
+        latest_event_and_cursor, stream_events, Contract, Error, Event, EventBody, Order,
+    },
 };
 
 use yew::{
@@ -10,27 +15,50 @@ use yew::{
     Callback, Properties,
 };
 
-use futures::future::join;
+use futures::{
+    channel::mpsc,
+    future::{abortable, join3, AbortHandle},
+    StreamExt,
+};
 
 #[derive(Default)]
 pub struct HistoryComp {
+    network: Network,
+    streams: Option<AbortHandle>,
     events: Vec<Event>,
+    route: Route,
+    lookup: Option<AbortHandle>,
+    lookup_error: Option<String>,
     selected_event: Option<Event>,
+    asc_error: Option<Error>,
+    desc_error: Option<Error>,
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct HistoryCompProps {
+    pub network: Network,
+    pub route: Route,
     pub onevent: Callback<SelectedEvent>,
 }
 
 pub struct SelectedEvent {
     pub event: Event,
     pub related: Vec<Event>,
+    pub deployments: Vec<Contract>,
 }
 
 pub enum HistoryCompMsg {
     Event(Event),
-    SelectEvent(Event),
+    /// The events stored in the cache from earlier visits.
+    Cached(Vec<Event>),
+    Status {
+        order: Order,
+        error: Option<Error>,
+    },
+    Fetched {
+        route: Route,
+        result: Result<Vec<Event>, Error>,
+    },
 }
 
 impl Component for HistoryComp {
@@ -38,69 +66,126 @@ impl Component for HistoryComp {
     type Properties = HistoryCompProps;
 
     fn create(ctx: &Context<Self>) -> Self {
-        let link_asc = ctx.link().clone();
-        let link_desc = ctx.link().clone();
-        wasm_bindgen_futures::spawn_local(async {
-            let (event, cursor) = latest_event_and_cursor(HORIZON_BASE_URL).await;
-            if let Some(event) = event {
-                link_asc.send_message(HistoryCompMsg::Event(event));
-            }
-            if let Some(cursor) = cursor {
-                join(
-                    collect_events(HORIZON_BASE_URL, &cursor, Order::Asc, Duration::from_secs(3), move |event| {
-                        link_asc.send_message(HistoryCompMsg::Event(event));
-                    }),
-                    collect_events(HORIZON_BASE_URL, &cursor, Order::Desc, Duration::from_secs(1), move |event| {
-                        link_desc.send_message(HistoryCompMsg::Event(event));
-                    }),
-                )
-                .await;
-            }
-        });
-        Self::default()
+        let mut comp = Self {
+            network: ctx.props().network.clone(),
+            streams: Some(Self::start(ctx)),
+            route: ctx.props().route.clone(),
+            ..Self::default()
+        };
+        comp.select_route(ctx);
+        comp
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let props = ctx.props();
+        if self.network.horizon_url != props.network.horizon_url {
+            self.destroy(ctx);
+            *self = Self {
+                network: props.network.clone(),
+                streams: Some(Self::start(ctx)),
+                route: props.route.clone(),
+                ..Self::default()
+            };
+            self.select_route(ctx);
+            true
+        } else if self.route != props.route {
+            self.network = props.network.clone();
+            self.route = props.route.clone();
+            self.select_route(ctx)
+        } else {
+            self.network = props.network.clone();
+            false
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some(streams) = self.streams.take() {
+            streams.abort();
+        }
+        if let Some(lookup) = self.lookup.take() {
+            lookup.abort();
+        }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            HistoryCompMsg::Event(e) => {
-                let found = self.events.binary_search_by(|f| f.id.cmp(&e.id).reverse());
-                let i = match found {
-                    Ok(i) | Err(i) => i,
-                };
-                self.events.insert(i, e);
-                true
+            HistoryCompMsg::Event(e) => self.insert(e),
+            HistoryCompMsg::Cached(events) => {
+                let mut changed = false;
+                for e in events {
+                    changed |= self.insert(e);
+                }
+                // The event being looked up may have been in the cache.
+                if changed && self.lookup.is_some() {
+                    if let Some(e) = self.find(&self.route) {
+                        if let Some(lookup) = self.lookup.take() {
+                            lookup.abort();
+                        }
+                        self.select(ctx, e);
+                    }
+                }
+                changed
             }
-            HistoryCompMsg::SelectEvent(e) => {
-                if self.selected_event.as_ref() == Some(&e) {
+            HistoryCompMsg::Status { order, error } => {
+                let status = match order {
+                    Order::Asc => &mut self.asc_error,
+                    Order::Desc => &mut self.desc_error,
+                };
+                if *status == error {
                     false
                 } else {
-                    self.selected_event = Some(e.clone());
-                    let related = self
-                        .events
-                        .iter()
-                        .filter(|r| r.contract_id() == e.contract_id())
-                        .cloned()
-                        .collect();
-                    ctx.props()
-                        .onevent
-                        .emit(SelectedEvent { event: e, related });
+                    *status = error;
                     true
                 }
             }
+            HistoryCompMsg::Fetched { route, result } => {
+                if route != self.route {
+                    return false;
+                }
+                self.lookup = None;
+                match result {
+                    Ok(events) => {
+                        for e in events {
+                            self.insert(e);
+                        }
+                        if let Some(e) = self.find(&route) {
+                            self.select(ctx, e);
+                        } else {
+                            self.lookup_error =
+                                Some(format!("{route} not found on {}", self.network.name));
+                        }
+                    }
+                    Err(err) => self.lookup_error = Some(format!("loading {route} failed: {err}")),
+                }
+                true
+            }
         }
     }
 
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        let scope = ctx.link();
+    fn view(&self, _ctx: &Context<Self>) -> Html {
         let events = self.events.clone();
         html! {
             <div class="component history">
+                {
+                    for [&self.asc_error, &self.desc_error].into_iter().flatten().map(|err| html! {
+                        <div class="status error">{ "connection problem, retrying: " }{ err.to_string() }</div>
+                    })
+                }
+                {
+                    if self.lookup.is_some() {
+                        html! { <div class="status">{ "loading " }{ self.route.to_string() }</div> }
+                    } else if let Some(err) = &self.lookup_error {
+                        html! { <div class="status error">{ err }</div> }
+                    } else {
+                        html!()
+                    }
+                }
                 <table>
                 <tr><th>{ "at" }</th><th>{ "tx" }</th><th>{ "op" }</th><th>{ "hash" }</th><th>{ "id" }</th></tr>
                 {
                     for events.into_iter().map(|e| {
                         let tx_hash = e.tx.clone();
-                        let tx_url = format!("{}/transactions/{}", HORIZON_BASE_URL, tx_hash);
+                        let tx_url = format!("{}/transactions/{}", self.network.horizon_url, tx_hash);
                         let selected = self.selected_event.as_ref().map(|e| &e.tx) == Some(&tx_hash);
                         match &e.body {
                             EventBody::Invocation(i) => {
@@ -109,24 +194,33 @@ impl Component for HistoryComp {
                                     <tr class={ if selected { "selected" } else { "" } }>
                                         <td>{ &e.at }</td>
                                         <td><a href={ tx_url } target="_blank">{ &e.tx[..7] }</a></td>
-                                        <td>{ "invoke" }</td>
+                                        <td>
+                                            { "invoke" }
+                                            {
+                                                if i.successful {
+                                                    html!()
+                                                } else {
+                                                    html! { <span class="badge error">{ "failed" }</span> }
+                                                }
+                                            }
+                                        </td>
                                         <td></td>
-                                        <td>{ &c_id[..7] }</td>
-                                        <td><button onclick={scope.callback(move |_| HistoryCompMsg::SelectEvent(e.clone()))}>{ "view" }</button></td>
+                                        <td><a href={ Route::Contract(c_id.clone()).to_string() }>{ &c_id[..7] }</a></td>
+                                        <td><a href={ Route::Op(e.id.clone()).to_string() }>{ "view" }</a></td>
                                     </tr>
                                 }
                             },
-                            EventBody::Deployment(c) => {
-                                let c_id = c.id.clone();
-                                let c_hash = c.hash();
+                            body => {
+                                let c_id = e.contract_id();
+                                let c_hash = body.deployed_contract().map(Contract::hash).unwrap_or_default();
                                 html!{
                                     <tr class={ if selected { "selected" } else { "" } }>
                                         <td>{ &e.at }</td>
                                         <td><a href={ tx_url } target="_blank">{ &e.tx[..7] }</a></td>
-                                        <td>{ "deploy" }</td>
-                                        <td>{ &c_hash[..7] }</td>
-                                        <td>{ &c_id[..7] }</td>
-                                        <td><button onclick={scope.callback(move |_| HistoryCompMsg::SelectEvent(e.clone()))}>{ "view" }</button></td>
+                                        <td>{ body.kind() }</td>
+                                        <td>{ c_hash.get(..7).unwrap_or_default() }</td>
+                                        <td><a href={ Route::Contract(c_id.clone()).to_string() }>{ &c_id[..7] }</a></td>
+                                        <td><a href={ Route::Contract(c_id.clone()).to_string() }>{ "view" }</a></td>
                                     </tr>
                                 }
                             },
@@ -138,3 +232,201 @@ impl Component for HistoryComp {
         }
     }
 }
+
+impl HistoryComp {
+    /// Inserts the event into the history, in order of id, if it isn't
+    /// already in it.
+    fn insert(&mut self, e: Event) -> bool {
+        match self.events.binary_search_by(|f| f.id.cmp(&e.id).reverse()) {
+            Ok(_) => false,
+            Err(i) => {
+                self.events.insert(i, e);
+                true
+            }
+        }
+    }
+
+    /// Returns the event the route points to, if it is in the history.
+    fn find(&self, route: &Route) -> Option<Event> {
+        self.events
+            .iter()
+            .find(|e| match route {
+                Route::Home | Route::Xdr => false,
+                Route::Tx(hash) => &e.tx == hash,
+                Route::Op(id) => &e.id == id,
+                Route::Contract(id) => e.body.is_deployment() && &e.contract_id() == id,
+            })
+            .cloned()
+    }
+
+    /// Selects the event the route points to, fetching it from the network if
+    /// it isn't in the history.
+    fn select_route(&mut self, ctx: &Context<Self>) -> bool {
+        if let Some(lookup) = self.lookup.take() {
+            lookup.abort();
+        }
+        self.lookup_error = None;
+        let route = self.route.clone();
+        if matches!(route, Route::Home | Route::Xdr) {
+            self.selected_event = None;
+            return true;
+        }
+        if let Some(e) = self.find(&route) {
+            self.select(ctx, e);
+            return true;
+        }
+        let base_url = self.network.horizon_url.clone();
+        let link = ctx.link().clone();
+        let (lookup, handle) = abortable(async move {
+            let result = match &route {
+                Route::Home | Route::Xdr => Ok(vec![]),
+                Route::Tx(hash) => get_transaction_events(&base_url, hash).await,
+                Route::Op(id) => get_operation_event(&base_url, id)
+                    .await
+                    .map(|e| e.into_iter().collect()),
+                Route::Contract(id) => {
+                    // The deployment of a contract can only be found by
+                    // walking the history back to it.
+                    let found = RefCell::new(vec![]);
+                    backfill_contract(&base_url, id, |e| found.borrow_mut().push(e), |_| {}).await;
+                    Ok(found.into_inner())
+                }
+            };
+            link.send_message(HistoryCompMsg::Fetched { route, result });
+        });
+        wasm_bindgen_futures::spawn_local(async {
+            let _ = lookup.await;
+        });
+        self.lookup = Some(handle);
+        true
+    }
+
+    /// Selects the event, passing it to the parent along with the events
+    /// related to it.
+    fn select(&mut self, ctx: &Context<Self>, e: Event) {
+        if self.selected_event.as_ref() != Some(&e) {
+            self.selected_event = Some(e.clone());
+            let related = self
+                .events
+                .iter()
+                .filter(|r| r.contract_id() == e.contract_id())
+                .cloned()
+                .collect();
+            let deployments = self
+                .events
+                .iter()
+                .filter_map(|r| r.body.deployed_contract().cloned())
+                .collect();
+            ctx.props().onevent.emit(SelectedEvent {
+                event: e,
+                related,
+                deployments,
+            });
+        }
+    }
+
+    fn start(ctx: &Context<Self>) -> AbortHandle {
+        let link = ctx.link().clone();
+        let network = ctx.props().network.clone();
+        let base_url = network.horizon_url.clone();
+        let (streams, handle) = abortable(async move {
+            // The history continues on from the cache if there is one, and
+            // otherwise without it.
+            let cache = Cache::open(&network).await.ok();
+            let mut cursors = Cursors::default();
+            if let Some(cache) = &cache {
+                if let Ok(events) = cache.events().await {
+                    link.send_message(HistoryCompMsg::Cached(events));
+                }
+                cursors = cache.cursors().await.unwrap_or_default();
+            }
+            let (newest, oldest) = match cursors {
+                Cursors {
+                    newest: Some(newest),
+                    oldest: Some(oldest),
+                } => (newest, oldest),
+                _ => {
+                    let (event, cursor) = loop {
+                        match latest_event_and_cursor(&base_url).await {
+                            Ok(latest) => break latest,
+                            Err(error) => {
+                                link.send_message(HistoryCompMsg::Status {
+                                    order: Order::Asc,
+                                    error: Some(error),
+                                });
+                                gloo_timers::future::sleep(Duration::from_secs(3)).await;
+                            }
+                        }
+                    };
+                    let cursor = match cursor {
+                        Some(cursor) => cursor,
+                        None => return,
+                    };
+                    if let Some(cache) = &cache {
+                        if let Some(event) = &event {
+                            let _ = cache.put_event(event, Order::Asc).await;
+                        }
+                        let _ = cache.put_cursor(Order::Asc, &cursor).await;
+                        let _ = cache.put_cursor(Order::Desc, &cursor).await;
+                    }
+                    if let Some(event) = event {
+                        link.send_message(HistoryCompMsg::Event(event));
+                    }
+                    (cursor.clone(), cursor)
+                }
+            };
+            // Events are stored one at a time in the order they arrive, so
+            // that the cursors never move past an event that isn't stored.
+            let (store, mut stored) = mpsc::unbounded::<(Event, Order)>();
+            let store_asc = store.clone();
+            let store_desc = store;
+            let (link_asc, link_asc_status) = (link.clone(), link.clone());
+            let (link_desc, link_desc_status) = (link.clone(), link);
+            join3(
+                stream_events(
+                    &base_url,
+                    &newest,
+                    Duration::from_secs(3),
+                    move |event| {
+                        let _ = store_asc.unbounded_send((event.clone(), Order::Asc));
+                        link_asc.send_message(HistoryCompMsg::Event(event));
+                    },
+                    move |error| {
+                        link_asc_status.send_message(HistoryCompMsg::Status {
+                            order: Order::Asc,
+                            error,
+                        });
+                    },
+                ),
+                collect_events(
+                    &base_url,
+                    &oldest,
+                    Order::Desc,
+                    Duration::from_secs(1),
+                    move |event| {
+                        let _ = store_desc.unbounded_send((event.clone(), Order::Desc));
+                        link_desc.send_message(HistoryCompMsg::Event(event));
+                    },
+                    move |error| {
+                        link_desc_status.send_message(HistoryCompMsg::Status {
+                            order: Order::Desc,
+                            error,
+                        });
+                    },
+                ),
+                async move {
+                    while let Some((event, order)) = stored.next().await {
+                        if let Some(cache) = &cache {
+                            let _ = cache.put_event(&event, order).await;
+                        }
+                    }
+                },
+            )
+            .await;
+        });
+        wasm_bindgen_futures::spawn_local(async {
+            let _ = streams.await;
+        });
+        handle
+    }
+}
@@ -1,11 +1,28 @@
-use crate::stream::{Contract, Event, EventBody};
-use crate::vm::invoke::invoke;
+use std::{fmt::Display, str::FromStr};
 
+use crate::ledger::get_ledger_entries;
+use crate::network::Network;
+use crate::spec::decode;
+use crate::stream::{backfill_contract, BackfillProgress, Contract, Error, Event, EventBody};
+use crate::strkey;
+use crate::tx::{invoke_envelope, TxParams};
+use crate::ui::args::ArgsComp;
+use crate::ui::budget::BudgetComp;
+use crate::vm::budget::BudgetReport;
+use crate::vm::convert::to_host;
+use crate::vm::diff::{FootprintDiff, StorageChange};
+use crate::vm::fee::FeeEstimate;
+use crate::vm::invoke::{invoke, Config, LedgerSnapshotSource};
+use crate::vm::replay::{contract_codes, replay, ReplayOutcome, ReplayStep};
+use crate::vm::trace::TraceEntry;
+
+use futures::future::{abortable, AbortHandle};
+
+use soroban_env_host::events::Events;
 use soroban_env_host::events::HostEvent;
-use soroban_env_host::{budget::Budget, events::Events, xdr::ReadXdr};
-use stellar_xdr::WriteXdr;
+use soroban_env_host::storage::SnapshotSource;
+use stellar_xdr::{LedgerEntry, LedgerFootprint, LedgerKey, ScVal};
 use web_sys::{HtmlInputElement, HtmlSelectElement};
-use yew::NodeRef;
 use yew::{
     events,
     prelude::{html, Component, Context, Html},
@@ -15,21 +32,125 @@ use yew::{
 #[derive(Default)]
 pub struct InvokeComp {
     function: Option<String>,
+    args: Option<Result<Vec<ScVal>, String>>,
+    state: State,
+    source_account: String,
     result: Option<String>,
-    budget: Option<Budget>,
+    changes: Vec<StorageChange>,
+    footprint: Option<LedgerFootprint>,
+    /// The function and args of the last successful simulation.
+    simulated: Option<(String, Vec<ScVal>)>,
+    sequence: String,
+    tx_fee: String,
+    exported: Option<Result<String, String>>,
+    replay: Vec<ReplayStep>,
+    ledger: LedgerFetch,
+    not_found: Vec<LedgerKey>,
+    calls: Vec<String>,
+    missing: Vec<String>,
+    budget: Option<BudgetReport>,
+    /// The budget of the simulation before the last of the same function.
+    previous_budget: Option<BudgetReport>,
+    /// The budgets of the simulations so far, by function.
+    budgets: Vec<(String, BudgetReport)>,
+    fee_estimate: Option<FeeEstimate>,
+    trace: Vec<TraceEntry>,
     events: Option<Events>,
+    backfilled: Vec<Event>,
+    backfill: Option<AbortHandle>,
+    backfill_progress: Option<BackfillProgress>,
+}
+
+/// The ledger entries fetched so far for simulating against ledger state.
+#[derive(Default)]
+struct LedgerFetch {
+    requested: Vec<LedgerKey>,
+    entries: Vec<(LedgerKey, LedgerEntry)>,
+}
+
+impl LedgerFetch {
+    /// Returns the keys requested that the endpoint had no entry for.
+    fn not_found(&self) -> Vec<LedgerKey> {
+        self.requested
+            .iter()
+            .filter(|k| !self.entries.iter().any(|(e, _)| &e == k))
+            .cloned()
+            .collect()
+    }
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct InvokeCompProps {
+    pub network: Network,
     pub contract: Contract,
     pub event: Event,
     pub related_events: Vec<Event>,
+    pub deployments: Vec<Contract>,
+}
+
+/// Where the state that an invocation runs against comes from.
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub enum State {
+    /// Rebuilt by replaying the related invocations seen in the history.
+    Replay,
+    /// Fetched from the ledger entry endpoint for the keys in the footprints
+    /// of the related invocations, and for any other keys the simulation
+    /// accesses.
+    Ledger,
+}
+
+impl State {
+    pub fn all() -> &'static [State] {
+        &[State::Replay, State::Ledger]
+    }
+}
+
+impl FromStr for State {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "replay" => Ok(State::Replay),
+            "ledger" => Ok(State::Ledger),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            State::Replay => write!(f, "replay"),
+            State::Ledger => write!(f, "ledger"),
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State::Replay
+    }
 }
 
 pub enum InvokeCompMsg {
-    SelectFunction { function: String },
-    Invoke { args: String },
+    SelectFunction {
+        function: String,
+    },
+    SelectState {
+        state: State,
+    },
+    SourceAccount(String),
+    Args(Result<Vec<ScVal>, String>),
+    Invoke,
+    InvokeWithLedgerEntries {
+        entries: Result<Vec<(LedgerKey, LedgerEntry)>, Error>,
+    },
+    Backfill,
+    BackfillEvent(Event),
+    BackfillProgress(BackfillProgress),
+    Sequence(String),
+    Fee(String),
+    Export,
 }
 
 impl Component for InvokeComp {
@@ -37,82 +158,186 @@ impl Component for InvokeComp {
     type Properties = InvokeCompProps;
 
     fn create(_ctx: &Context<Self>) -> Self {
-        Self::default()
+        Self {
+            source_account: strkey::encode_public_key(&[0; 32]),
+            tx_fee: "100".to_string(),
+            ..Self::default()
+        }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             InvokeCompMsg::SelectFunction { function } => {
-                self.function = Some(function);
+                self.function = Some(function).filter(|f| !f.is_empty());
+                self.args = None;
+                true
+            }
+            InvokeCompMsg::Args(args) => {
+                self.args = Some(args);
+                true
+            }
+            InvokeCompMsg::SelectState { state } => {
+                self.state = state;
                 false
             }
-            InvokeCompMsg::Invoke { args } => {
-                if let Some(function) = &self.function {
-                    let props = ctx.props();
-                    let contract = &props.contract;
-                    let mut related_events = props.related_events.clone();
-                    related_events.sort_by(|a, b| a.id.cmp(&b.id));
-                    let related_invocations = related_events
-                        .iter()
-                        .filter_map(|e| {
-                            if let EventBody::Invocation(i) = &e.body {
-                                Some(i.clone())
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>();
-                    let mut storage = None;
-                    for i in related_invocations {
-                        let (_, new_storage, _, _) = invoke(
-                            storage,
-                            contract.bytes.clone(),
-                            contract.id.clone(),
-                            function.clone(),
-                            i.args
-                                .iter()
-                                .map(|a| match a {
-                                    Some(a) => a.clone(),
-                                    None => stellar_xdr::ScVal::Static(stellar_xdr::ScStatic::Void),
-                                })
-                                .map(|a| {
-                                    soroban_env_host::xdr::ScVal::from_xdr_base64(
-                                        a.to_xdr_base64().unwrap(),
-                                    )
-                                    .unwrap()
-                                })
-                                .collect(),
-                        );
-                        storage = Some(new_storage);
-                    }
-                    let (result, _, budget, events) = invoke(
-                        storage,
-                        contract.bytes.clone(),
-                        contract.id.clone(),
-                        function.clone(),
-                        serde_json::from_str(&args).unwrap(),
+            InvokeCompMsg::SourceAccount(source_account) => {
+                self.source_account = source_account;
+                false
+            }
+            InvokeCompMsg::Invoke => match self.state {
+                State::Replay => {
+                    let config = match self.config(ctx) {
+                        Ok(config) => config,
+                        Err(err) => {
+                            self.result = Some(err);
+                            return true;
+                        }
+                    };
+                    let (storage, replay) = replay(
+                        &config,
+                        &ctx.props().contract,
+                        &self.related_events(ctx),
+                        &ctx.props().deployments,
                     );
-                    self.result = Some(result);
-                    self.budget = Some(budget);
-                    self.events = Some(events);
+                    self.replay = replay;
+                    self.not_found = vec![];
+                    self.invoke(ctx, storage.map(|s| Box::new(s) as Box<dyn SnapshotSource>))
+                        .is_some()
+                }
+                State::Ledger => {
+                    self.ledger = LedgerFetch::default();
+                    let keys = footprint_keys(&self.related_events(ctx));
+                    self.fetch_ledger_entries(ctx, keys);
+                    false
+                }
+            },
+            InvokeCompMsg::InvokeWithLedgerEntries { entries } => {
+                let entries = match entries {
+                    Ok(entries) => entries,
+                    Err(err) => {
+                        self.result = Some(format!("getting ledger entries failed: {err}"));
+                        return true;
+                    }
+                };
+                self.ledger.entries.extend(entries);
+                let entries = self
+                    .ledger
+                    .entries
+                    .iter()
+                    .filter_map(|(k, e)| Some((to_host(k)?, to_host(e)?)))
+                    .collect();
+                self.replay = vec![];
+                let recorded = match self.invoke(ctx, Some(Box::new(LedgerSnapshotSource(entries))))
+                {
+                    Some(recorded) => recorded,
+                    None => return false,
+                };
+                // Keys in the recorded footprint that have not been requested
+                // yet are fetched and the simulation re-run, until the
+                // footprint has no new keys.
+                let missing = recorded
+                    .into_iter()
+                    .filter(|k| !self.ledger.requested.contains(k))
+                    .collect::<Vec<_>>();
+                if missing.is_empty() {
+                    self.not_found = self.ledger.not_found();
                     true
                 } else {
+                    self.fetch_ledger_entries(ctx, missing);
                     false
                 }
             }
+            InvokeCompMsg::Backfill => {
+                if let Some(handle) = self.backfill.take() {
+                    handle.abort();
+                }
+                let base_url = ctx.props().network.horizon_url.clone();
+                let contract_id = ctx.props().contract.id.clone();
+                let link_event = ctx.link().clone();
+                let link_progress = ctx.link().clone();
+                let (backfill, handle) = abortable(async move {
+                    backfill_contract(
+                        &base_url,
+                        &contract_id,
+                        move |event| link_event.send_message(InvokeCompMsg::BackfillEvent(event)),
+                        move |progress| {
+                            link_progress.send_message(InvokeCompMsg::BackfillProgress(progress))
+                        },
+                    )
+                    .await
+                });
+                wasm_bindgen_futures::spawn_local(async {
+                    let _ = backfill.await;
+                });
+                self.backfill = Some(handle);
+                self.backfilled = vec![];
+                self.backfill_progress = None;
+                true
+            }
+            InvokeCompMsg::BackfillEvent(e) => {
+                self.backfilled.push(e);
+                false
+            }
+            InvokeCompMsg::BackfillProgress(progress) => {
+                self.backfill_progress = Some(progress);
+                true
+            }
+            InvokeCompMsg::Sequence(sequence) => {
+                self.sequence = sequence;
+                false
+            }
+            InvokeCompMsg::Fee(fee) => {
+                self.tx_fee = fee;
+                false
+            }
+            InvokeCompMsg::Export => {
+                self.exported = Some(self.export(ctx));
+                true
+            }
         }
     }
 
     fn changed(&mut self, _ctx: &Context<Self>) -> bool {
         self.function = None;
+        self.args = None;
         self.result = None;
+        self.changes = vec![];
+        self.footprint = None;
+        self.simulated = None;
+        self.exported = None;
+        self.replay = vec![];
+        self.ledger = LedgerFetch::default();
+        self.not_found = vec![];
+        self.calls = vec![];
+        self.missing = vec![];
         self.budget = None;
+        self.previous_budget = None;
+        self.budgets = vec![];
+        self.fee_estimate = None;
+        self.trace = vec![];
         self.events = None;
+        if let Some(handle) = self.backfill.take() {
+            handle.abort();
+        }
+        self.backfilled = vec![];
+        self.backfill_progress = None;
         true
     }
 
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some(handle) = self.backfill.take() {
+            handle.abort();
+        }
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
         let scope = ctx.link();
+        let onchange_state = {
+            scope.callback(|e: events::Event| InvokeCompMsg::SelectState {
+                state: State::from_str(&e.target_unchecked_into::<HtmlSelectElement>().value())
+                    .unwrap_or_default(),
+            })
+        };
         let props = ctx.props();
         let contract = &props.contract;
         let functions = contract.fns();
@@ -127,32 +352,40 @@ impl Component for InvokeComp {
         } else {
             vec![]
         };
-        let budget = if let Some(budget) = &self.budget {
-            format!(
-                "cpu: {} mem: {}",
-                budget.get_cpu_insns_count(),
-                budget.get_mem_bytes_count()
-            )
-        } else {
-            String::new()
-        };
         let onchange = {
             scope.callback(|e: events::Event| InvokeCompMsg::SelectFunction {
                 function: e.target_unchecked_into::<HtmlSelectElement>().value(),
             })
         };
-        let args_ref = NodeRef::default();
-        let args_ref_in_html = args_ref.clone();
-        let onclick = {
-            ctx.link().callback(move |_| InvokeCompMsg::Invoke {
-                args: {
-                    args_ref
-                        .cast::<HtmlInputElement>()
-                        .map(|n| n.value())
-                        .unwrap_or_default()
-                },
-            })
+        let onclick_backfill = scope.callback(|_| InvokeCompMsg::Backfill);
+        let backfill = match &self.backfill_progress {
+            Some(BackfillProgress {
+                error: Some(err),
+                done: true,
+                ..
+            }) => format!("stopped after error: {err}"),
+            Some(BackfillProgress {
+                error: Some(err), ..
+            }) => format!("retrying after error: {err}"),
+            Some(p) if p.done => format!("complete, {} pages, {} events found", p.pages, p.found),
+            Some(p) => format!("in progress, {} pages, {} events found", p.pages, p.found),
+            None if self.backfill.is_some() => "starting".to_string(),
+            None => String::new(),
         };
+        let oninput_source = scope.callback(|e: events::InputEvent| {
+            InvokeCompMsg::SourceAccount(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let oninput_sequence = scope.callback(|e: events::InputEvent| {
+            InvokeCompMsg::Sequence(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let oninput_fee = scope.callback(|e: events::InputEvent| {
+            InvokeCompMsg::Fee(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onclick_export = scope.callback(|_| InvokeCompMsg::Export);
+        let onclick = scope.callback(|_| InvokeCompMsg::Invoke);
+        let onargs = scope.callback(InvokeCompMsg::Args);
+        let function_spec = self.function.as_ref().and_then(|f| contract.fn_spec(f));
+        let args_valid = matches!(self.args, Some(Ok(_)));
         html! {
             <div class="component invoke">
                 <strong>{ "function: " }</strong>
@@ -160,29 +393,399 @@ impl Component for InvokeComp {
                     <option value="">{ "-- select a function --" }</option>
                     {
                         for functions.iter().map(|f| {
-                            html! { <option value={f.clone()}>{f}</option> }
+                            html! { <option value={f.clone()} selected={self.function.as_ref() == Some(f)}>{f}</option> }
+                        })
+                    }
+                </select>
+                <button {onclick} disabled={!args_valid}>{ "invoke" }</button>
+                <br/>
+                <strong>{ "state: " }</strong>
+                <select onchange={onchange_state}>
+                    {
+                        for State::all().iter().map(|s| {
+                            html! { <option value={s.to_string()} selected={s == &self.state}>{s}</option> }
                         })
                     }
                 </select>
-                <button {onclick}>{ "invoke" }</button>
+                <button onclick={onclick_backfill}>{ "backfill history" }</button>
+                { " " }{ backfill }
+                <br/>
+                <strong>{ "source account: " }</strong>
+                <input type="text" class="account" value={self.source_account.clone()} oninput={oninput_source} />
                 <br/>
-                <strong>{ "args: " }</strong>{ " (json array of scvals)"}
+                <strong>{ "args: " }</strong>
                 <br/>
-                <textarea ref={args_ref_in_html} value="[]" />
+                {
+                    if let Some(function) = function_spec {
+                        html! { <ArgsComp {function} entries={contract.spec().unwrap_or_default()} onchange={onargs} /> }
+                    } else {
+                        html!()
+                    }
+                }
+                {
+                    if let Some(Err(err)) = &self.args {
+                        html! { <div class="error">{ err }</div> }
+                    } else {
+                        html!()
+                    }
+                }
                 <br/>
                 <hr/>
                 <strong>{ "result: " }</strong>
                 <br/>
                 <pre><code>{ self.result.clone().unwrap_or_default() }</code></pre>
                 <br/>
+                <strong>{ "footprint: " }</strong>{ " (recorded by the simulation)" }
+                <br/>
+                <pre><code class="language-json">{ self.footprint.as_ref().map(|f| serde_json::to_string_pretty(f).unwrap_or_default()).unwrap_or_default() }</code></pre>
+                <br/>
+                <strong>{ "storage changes: " }</strong>
+                <br/>
+                <table>
+                <tr><th>{ "change" }</th><th>{ "key" }</th><th>{ "before" }</th><th>{ "after" }</th></tr>
+                {
+                    for self.changes.iter().map(|c| html! {
+                        <tr class={ c.kind() }>
+                            <td>{ c.kind() }</td>
+                            <td><code>{ decode::ledger_key(c.key()) }</code></td>
+                            <td><code>{ c.before().map(decode::ledger_entry).unwrap_or_default() }</code></td>
+                            <td><code>{ c.after().map(decode::ledger_entry).unwrap_or_default() }</code></td>
+                        </tr>
+                    })
+                }
+                </table>
+                <br/>
+                <strong>{ "export transaction: " }</strong>{ " (unsigned, for the source account above)" }
+                <br/>
+                { "sequence number: " }
+                <input type="text" value={self.sequence.clone()} oninput={oninput_sequence} />
+                { " fee: " }
+                <input type="text" value={self.tx_fee.clone()} oninput={oninput_fee} />
+                <button onclick={onclick_export} disabled={self.simulated.is_none()}>{ "export" }</button>
+                <br/>
+                {
+                    match &self.exported {
+                        Some(Ok(envelope)) => html! { <pre><code>{ envelope }</code></pre> },
+                        Some(Err(err)) => html! { <div class="error">{ err }</div> },
+                        None => html!(),
+                    }
+                }
+                <br/>
+                {
+                    if self.not_found.is_empty() {
+                        html!()
+                    } else {
+                        html! {
+                            <>
+                                <strong>{ "ledger entries not found: " }</strong>{ " (the ledger entry endpoint returned 404)" }
+                                <br/>
+                                <pre><code>{
+                                    self.not_found
+                                        .iter()
+                                        .map(|k| serde_json::to_string(k).unwrap_or_default())
+                                        .collect::<Vec<_>>()
+                                        .join("\n")
+                                }</code></pre>
+                                <br/>
+                            </>
+                        }
+                    }
+                }
+                <strong>{ "contracts called: " }</strong>{ " (in the order first called, not nested by caller)" }
+                <br/>
+                <ol>
+                    { for self.calls.iter().map(|c| html! { <li>{ c }</li> }) }
+                </ol>
+                {
+                    for self.missing.iter().map(|m| html! {
+                        <>{ "called contract not deployed in the history: " }{ m }<br/></>
+                    })
+                }
+                <br/>
                 <strong>{ "events: " }</strong>
                 <br/>
                 <pre><code>{ events }</code></pre>
                 <br/>
+                <strong>{ "log: " }</strong>{ " (the call, contract loads, then logs and events, then the return; host function calls are not reported by the host)" }
+                <br/>
+                <ol class="trace">
+                    { for self.trace.iter().map(trace_entry) }
+                </ol>
+                <br/>
                 <strong>{ "budget: " }</strong>
                 <br/>
-                <pre><code>{ budget }</code></pre>
+                {
+                    if let Some(report) = &self.budget {
+                        html! { <BudgetComp report={report.clone()} previous={self.previous_budget.clone()} limits={props.network.limits.clone()} /> }
+                    } else {
+                        html!()
+                    }
+                }
+                <br/>
+                <strong>{ "fee estimate: " }</strong>{ " (stroops, using assumed fee rates, not ones read from the network)" }
+                <br/>
+                {
+                    if let Some(fee) = &self.fee_estimate {
+                        html! {
+                            <table>
+                            <tr><th>{ "resource" }</th><th>{ "fee" }</th></tr>
+                            {
+                                for fee.parts.iter().map(|(name, fee)| html! {
+                                    <tr><td>{ name }</td><td>{ fee }</td></tr>
+                                })
+                            }
+                            <tr><th>{ "total" }</th><th>{ fee.total() }</th></tr>
+                            </table>
+                        }
+                    } else {
+                        html!()
+                    }
+                }
+                <br/>
+                <strong>{ "replay: " }</strong>{ " (related invocations replayed to rebuild state)" }
+                <br/>
+                <table>
+                <tr><th>{ "op" }</th><th>{ "function" }</th><th>{ "outcome" }</th><th>{ "footprint" }</th><th>{ "est. fee" }</th><th>{ "fee charged" }</th><th>{ "max fee" }</th></tr>
+                {
+                    for self.replay.iter().map(|s| {
+                        let outcome = match &s.outcome {
+                            ReplayOutcome::SkippedFailed => "skipped: failed on chain".to_string(),
+                            ReplayOutcome::SkippedUndecodableArgs => "skipped: undecodable args".to_string(),
+                            ReplayOutcome::Replayed { result } => format!("ok: {result}"),
+                            ReplayOutcome::Errored { error } => format!("error: {error}"),
+                        };
+                        html! {
+                            <tr>
+                                <td>{ &s.op }</td>
+                                <td>{ &s.function }</td>
+                                <td>{ outcome }</td>
+                                <td>{ footprint_summary(s.footprint.as_ref()) }</td>
+                                <td>{ s.fee.as_ref().map(|f| f.total().to_string()).unwrap_or_default() }</td>
+                                <td>{ s.fee_charged.map(|f| f.to_string()).unwrap_or_default() }</td>
+                                <td>{ s.max_fee.map(|f| f.to_string()).unwrap_or_default() }</td>
+                            </tr>
+                        }
+                    })
+                }
+                </table>
             </div>
         }
     }
 }
+
+impl InvokeComp {
+    /// Returns the related events merged with any events found by a backfill.
+    fn related_events(&self, ctx: &Context<Self>) -> Vec<Event> {
+        let mut events = ctx.props().related_events.clone();
+        for e in &self.backfilled {
+            if !events.iter().any(|r| r.id == e.id) {
+                events.push(e.clone());
+            }
+        }
+        events
+    }
+
+    /// Returns the config for the host from the selected network and the
+    /// source account entered.
+    fn config(&self, ctx: &Context<Self>) -> Result<Config, String> {
+        let source_account = strkey::decode_public_key(self.source_account.trim())
+            .map_err(|err| format!("invalid source account: {err}"))?;
+        Ok(Config {
+            network_passphrase: ctx.props().network.passphrase.clone(),
+            source_account,
+            sequence_number: 0,
+            timestamp: 0,
+            fee_rates: ctx.props().network.fee_rates.clone(),
+        })
+    }
+
+    fn fetch_ledger_entries(&mut self, ctx: &Context<Self>, keys: Vec<LedgerKey>) {
+        self.ledger.requested.extend(keys.iter().cloned());
+        let ledger_url = ctx.props().network.ledger_url.clone();
+        let link = ctx.link().clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let entries = get_ledger_entries(&ledger_url, &keys).await;
+            link.send_message(InvokeCompMsg::InvokeWithLedgerEntries { entries });
+        });
+    }
+
+    /// Returns the last successful simulation as an unsigned transaction.
+    fn export(&self, ctx: &Context<Self>) -> Result<String, String> {
+        let (function, args) = self
+            .simulated
+            .as_ref()
+            .ok_or("simulate a successful invocation first")?;
+        let footprint = self.footprint.as_ref().ok_or("no footprint was recorded")?;
+        let params = TxParams {
+            source_account: strkey::decode_public_key(self.source_account.trim())
+                .map_err(|err| format!("invalid source account: {err}"))?,
+            seq_num: self
+                .sequence
+                .trim()
+                .parse()
+                .map_err(|err| format!("invalid sequence number: {err}"))?,
+            fee: self
+                .tx_fee
+                .trim()
+                .parse()
+                .map_err(|err| format!("invalid fee: {err}"))?,
+        };
+        invoke_envelope(&params, &ctx.props().contract.id, function, args, footprint)
+    }
+
+    /// Simulates the selected function with the args entered, returning the
+    /// keys in the footprint it recorded, or None if there was nothing to
+    /// simulate.
+    fn invoke(
+        &mut self,
+        ctx: &Context<Self>,
+        source: Option<Box<dyn SnapshotSource>>,
+    ) -> Option<Vec<LedgerKey>> {
+        if let (Some(function), Some(Ok(args))) = (&self.function, &self.args) {
+            let simulated = (function.clone(), args.clone());
+            let config = match self.config(ctx) {
+                Ok(config) => config,
+                Err(err) => {
+                    self.result = Some(err);
+                    return Some(vec![]);
+                }
+            };
+            let args = match args.iter().map(to_host).collect::<Option<Vec<_>>>() {
+                Some(args) => args,
+                None => {
+                    self.result = Some("args could not be converted for the host".to_string());
+                    return Some(vec![]);
+                }
+            };
+            let props = ctx.props();
+            let contract = &props.contract;
+            let invoked = invoke(
+                &config,
+                source,
+                contract_codes(contract, &props.deployments),
+                contract.id.clone(),
+                function.clone(),
+                args,
+            );
+            // A failed invocation has its changes rolled back on chain.
+            self.changes = if invoked.result.is_ok() {
+                invoked.changes
+            } else {
+                vec![]
+            };
+            let report = BudgetReport::from(&invoked.budget);
+            self.previous_budget = self
+                .budgets
+                .iter()
+                .rev()
+                .find(|(f, _)| f == &simulated.0)
+                .map(|(_, r)| r.clone());
+            self.budgets.push((simulated.0.clone(), report.clone()));
+            self.budget = Some(report);
+            self.fee_estimate = Some(invoked.fee);
+            self.trace = invoked.trace;
+            self.simulated = invoked.result.is_ok().then_some(simulated);
+            self.exported = None;
+            self.result = Some(match invoked.result {
+                Ok(result) => serde_json::to_string_pretty(&result).unwrap_or_default(),
+                Err(err) => err.to_string(),
+            });
+            self.footprint = invoked.footprint;
+            self.calls = invoked.calls;
+            self.missing = invoked.missing;
+            self.events = Some(invoked.events);
+            Some(
+                self.footprint
+                    .iter()
+                    .flat_map(|f| f.read_only.iter().chain(f.read_write.iter()))
+                    .cloned()
+                    .collect(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+fn trace_entry(entry: &TraceEntry) -> Html {
+    let (class, text) = match entry {
+        TraceEntry::Call {
+            contract,
+            function,
+            args,
+        } => (
+            "call",
+            format!(
+                "call {contract}.{function}({})",
+                args.iter()
+                    .map(decode::untyped)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        TraceEntry::Load {
+            contract,
+            missing: false,
+        } => ("load", format!("load {contract}")),
+        TraceEntry::Load {
+            contract,
+            missing: true,
+        } => (
+            "load error",
+            format!("load {contract}: code not in the history"),
+        ),
+        TraceEntry::Log(log) => ("log", format!("log: {log}")),
+        TraceEntry::Event(e) => ("event", format!("event: {}", decode::event(e))),
+        TraceEntry::Return(Ok(v)) => ("return", format!("return {}", decode::untyped(v))),
+        TraceEntry::Return(Err(err)) => ("return error", format!("error: {err}")),
+    };
+    html! { <li {class}><code>{ text }</code></li> }
+}
+
+/// Returns a description of the differences between the footprint recorded
+/// when replaying and the footprint on chain.
+fn footprint_summary(diff: Option<&FootprintDiff>) -> Html {
+    let diff = match diff {
+        Some(diff) => diff,
+        None => return html!(),
+    };
+    if diff.is_empty() {
+        return html! { <>{ "matches" }</> };
+    }
+    let keys = |label: &str, keys: &[LedgerKey]| {
+        keys.iter()
+            .map(|k| format!("{label}: {}", decode::ledger_key(k)))
+            .collect::<Vec<_>>()
+    };
+    let lines = [
+        keys("missing read-only", &diff.missing_read_only),
+        keys("missing read-write", &diff.missing_read_write),
+        keys("unused", &diff.unused),
+    ]
+    .concat();
+    html! {
+        <span class="error">{ "mismatch" }<pre><code>{ lines.join("\n") }</code></pre></span>
+    }
+}
+
+/// Returns the keys in the footprints of the invocations in the events,
+/// without duplicates.
+fn footprint_keys(events: &[Event]) -> Vec<LedgerKey> {
+    let mut keys: Vec<LedgerKey> = vec![];
+    for e in events {
+        if let EventBody::Invocation(i) = &e.body {
+            if let Some(LedgerFootprint {
+                read_only,
+                read_write,
+            }) = &i.footprint
+            {
+                for k in read_only.iter().chain(read_write.iter()) {
+                    if !keys.contains(k) {
+                        keys.push(k.clone());
+                    }
+                }
+            }
+        }
+    }
+    keys
+}
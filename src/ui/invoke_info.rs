@@ -1,46 +1,304 @@
-use crate::stream::Invocation;
+use std::{fmt::Display, str::FromStr};
 
+use crate::network::Network;
+use crate::spec::decode;
+use crate::stream::{Contract, Event, Invocation};
+use crate::vm::diff::meta_changes;
+use crate::vm::invoke::Config;
+use crate::vm::replay::{rerun, Rerun};
+
+use web_sys::HtmlSelectElement;
 use yew::{
+    events,
     prelude::{html, Component, Context, Html},
-    Properties,
+    Properties, TargetCast,
 };
 
 #[derive(Default)]
-pub struct InvokeInfoComp;
+pub struct InvokeInfoComp {
+    format: Format,
+    rerun: Option<Result<Rerun, String>>,
+}
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct InvokeInfoCompProps {
+    pub network: Network,
+    pub event: Event,
     pub invocation: Invocation,
+    pub related_events: Vec<Event>,
+    pub deployments: Vec<Contract>,
+    /// The deployment of the invoked contract, if known, which provides the
+    /// spec for decoding.
+    pub contract: Option<Contract>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Format {
+    Decoded,
+    Raw,
+}
+
+impl Format {
+    pub fn all() -> &'static [Format] {
+        &[Format::Decoded, Format::Raw]
+    }
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decoded" => Ok(Format::Decoded),
+            "raw" => Ok(Format::Raw),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Decoded => write!(f, "decoded"),
+            Format::Raw => write!(f, "raw"),
+        }
+    }
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Decoded
+    }
+}
+
+pub enum InvokeInfoCompMsg {
+    SelectFormat { format: Format },
+    Rerun,
 }
 
 impl Component for InvokeInfoComp {
-    type Message = ();
+    type Message = InvokeInfoCompMsg;
     type Properties = InvokeInfoCompProps;
 
     fn create(_ctx: &Context<Self>) -> Self {
         Self::default()
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, _msg: Self::Message) -> bool {
-        false
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            InvokeInfoCompMsg::SelectFormat { format } => {
+                self.format = format;
+                true
+            }
+            InvokeInfoCompMsg::Rerun => {
+                let props = ctx.props();
+                self.rerun = Some(match &props.contract {
+                    Some(contract) => rerun(
+                        &Config {
+                            network_passphrase: props.network.passphrase.clone(),
+                            source_account: [0; 32],
+                            sequence_number: 0,
+                            timestamp: 0,
+                            fee_rates: props.network.fee_rates.clone(),
+                        },
+                        contract,
+                        &props.event,
+                        &props.related_events,
+                        &props.deployments,
+                    ),
+                    None => Err("contract deployment not in history".to_string()),
+                });
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, _ctx: &Context<Self>) -> bool {
+        self.rerun = None;
+        true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        let scope = ctx.link();
         let props = ctx.props();
         let invocation = &props.invocation;
+        let onchange = {
+            scope.callback(|e: events::Event| InvokeInfoCompMsg::SelectFormat {
+                format: Format::from_str(&e.target_unchecked_into::<HtmlSelectElement>().value())
+                    .unwrap_or_default(),
+            })
+        };
         html! {
             <div class="component invocationinfo">
                 <strong>{ "contract id: " }</strong>{ &invocation.id }<br/>
                 <strong>{ "function: " }</strong>{ &invocation.function }<br/>
+                <strong>{ "fee charged: " }</strong>{ invocation.fee_charged.map(|f| f.to_string()).unwrap_or_default() }
+                { " max fee: " }{ invocation.max_fee.map(|f| f.to_string()).unwrap_or_default() }<br/>
+                {
+                    if invocation.successful {
+                        html!()
+                    } else {
+                        html! {
+                            <>
+                                <strong class="error">{ "failed: " }</strong><br/>
+                                <pre><code>
+                                    {
+                                        invocation
+                                            .failure
+                                            .as_ref()
+                                            .map(decode::failure)
+                                            .unwrap_or_else(|| "no reason recorded".to_string())
+                                    }
+                                </code></pre>
+                            </>
+                        }
+                    }
+                }
+                <select {onchange}>
+                    {
+                        for Format::all().iter().map(|f| {
+                            html! { <option value={f.to_string()} selected={f == &self.format}>{f}</option> }
+                        })
+                    }
+                </select><br/>
+                {
+                    match self.format {
+                        Format::Decoded => Self::view_decoded(invocation, props.contract.as_ref()),
+                        Format::Raw => Self::view_raw(invocation),
+                    }
+                }
+                <strong>{ "footprint: " }</strong><br/>
+                <pre><code class="language-json">{ serde_json::to_string_pretty(&invocation.footprint).unwrap_or_default() }</code></pre>
+                <strong>{ "storage changes: " }</strong>{ " (recorded on chain)" }<br/>
+                {
+                    match &invocation.changes {
+                        Some(changes) => html! {
+                            <table>
+                            <tr><th>{ "change" }</th><th>{ "key" }</th><th>{ "before" }</th><th>{ "after" }</th></tr>
+                            {
+                                for meta_changes(changes).iter().map(|c| html! {
+                                    <tr class={ c.kind() }>
+                                        <td>{ c.kind() }</td>
+                                        <td><code>{ decode::ledger_key(c.key()) }</code></td>
+                                        <td><code>{ c.before().map(decode::ledger_entry).unwrap_or_default() }</code></td>
+                                        <td><code>{ c.after().map(decode::ledger_entry).unwrap_or_default() }</code></td>
+                                    </tr>
+                                })
+                            }
+                            </table>
+                        },
+                        None => html! { <>{ "unknown" }<br/></> },
+                    }
+                }
+                <button onclick={scope.callback(|_| InvokeInfoCompMsg::Rerun)}>{ "re-run" }</button>
+                { " (simulates the invocation against the state replayed from the invocations before it, and compares it with the chain)" }
+                <br/>
+                {
+                    match &self.rerun {
+                        Some(Ok(rerun)) => Self::view_rerun(rerun),
+                        Some(Err(err)) => html! { <div class="error">{ err }</div> },
+                        None => html!(),
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+impl InvokeInfoComp {
+    fn view_rerun(rerun: &Rerun) -> Html {
+        let verdict = |matches: bool| {
+            if matches {
+                html! { <span>{ "matches" }</span> }
+            } else {
+                html! { <span class="error">{ "diverges" }</span> }
+            }
+        };
+        let result = match &rerun.result {
+            Ok(v) => decode::untyped(v),
+            Err(err) => format!("error: {err}"),
+        };
+        let events = rerun
+            .events
+            .iter()
+            .map(decode::event)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        html! {
+            <>
+                <strong>{ "re-run result: " }</strong>{ verdict(rerun.result_matches) }<br/>
+                <pre><code>{ result }</code></pre>
+                <strong>{ "re-run events: " }</strong>{ rerun.events_match.map(verdict).unwrap_or_else(|| html! { <span>{ "unknown" }</span> }) }<br/>
+                <pre><code>{ events }</code></pre>
+            </>
+        }
+    }
+
+    fn view_decoded(invocation: &Invocation, contract: Option<&Contract>) -> Html {
+        let entries = contract.and_then(|c| c.spec().ok()).unwrap_or_default();
+        let function = contract.and_then(|c| c.fn_spec(&invocation.function));
+        let args = invocation
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let input = function.as_ref().and_then(|f| f.inputs.get(i));
+                let name = input
+                    .map(|i| i.name.to_string_lossy())
+                    .unwrap_or_else(|| format!("arg{i}"));
+                let value = match (a, input) {
+                    (Some(a), Some(input)) => decode::to_string(a, &input.type_, &entries),
+                    (Some(a), None) => decode::untyped(a),
+                    (None, _) => "<undecodable>".to_string(),
+                };
+                format!("{name}: {value}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let result = match (
+            &invocation.result,
+            function.as_ref().and_then(|f| f.outputs.first()),
+        ) {
+            (Some(r), Some(t)) => decode::to_string(r, t, &entries),
+            (Some(r), None) => decode::untyped(r),
+            (None, _) => String::new(),
+        };
+        let events = invocation
+            .events
+            .iter()
+            .flatten()
+            .map(decode::event)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        html! {
+            <>
+                {
+                    if contract.is_none() {
+                        html! { <>{ "contract deployment not in history, values decoded without spec" }<br/></> }
+                    } else {
+                        html!()
+                    }
+                }
+                <strong>{ "args: " }</strong><br/>
+                <pre><code>{ args }</code></pre>
+                <strong>{ "result: " }</strong><br/>
+                <pre><code>{ result }</code></pre>
+                <strong>{ "events: " }</strong><br/>
+                <pre><code>{ events }</code></pre>
+            </>
+        }
+    }
+
+    fn view_raw(invocation: &Invocation) -> Html {
+        html! {
+            <>
                 <strong>{ "args: " }</strong><br/>
                 <pre><code class="language-json">{ serde_json::to_string_pretty(&invocation.args).unwrap_or_default() }</code></pre>
                 <strong>{ "result: " }</strong><br/>
                 <pre><code class="language-json">{ serde_json::to_string_pretty(&invocation.result).unwrap_or_default() }</code></pre>
                 <strong>{ "events: " }</strong><br/>
                 <pre><code class="language-json">{ serde_json::to_string_pretty(&invocation.events).unwrap_or_default() }</code></pre>
-                <strong>{ "footprint: " }</strong><br/>
-                <pre><code class="language-json">{ serde_json::to_string_pretty(&invocation.footprint).unwrap_or_default() }</code></pre>
-            </div>
+            </>
         }
     }
 }
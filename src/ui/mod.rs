@@ -1,5 +1,11 @@
+pub mod args;
+pub mod budget;
+pub mod contract;
 pub mod contract_info;
 pub mod event_info;
 pub mod history;
 pub mod invoke;
 pub mod invoke_info;
+pub mod network;
+pub mod transaction;
+pub mod xdr;
@@ -0,0 +1,143 @@
+use crate::network::Network;
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{
+    events,
+    prelude::{html, Component, Context, Html},
+    Callback, Properties, TargetCast,
+};
+
+#[derive(Default)]
+pub struct NetworkComp {
+    custom: bool,
+    horizon_url: String,
+    passphrase: String,
+    /// The ledger entry endpoint, which is set for any network since none of
+    /// the networks provide one.
+    ledger_url: String,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct NetworkCompProps {
+    pub network: Network,
+    pub onchange: Callback<Network>,
+}
+
+pub enum NetworkCompMsg {
+    Select { name: String },
+    HorizonUrl(String),
+    Passphrase(String),
+    LedgerUrl(String),
+    Use,
+}
+
+impl Component for NetworkComp {
+    type Message = NetworkCompMsg;
+    type Properties = NetworkCompProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let network = &ctx.props().network;
+        Self {
+            custom: network.name == "custom",
+            horizon_url: network.horizon_url.clone(),
+            passphrase: network.passphrase.clone(),
+            ledger_url: network.ledger_url.clone(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            NetworkCompMsg::Select { name } => {
+                if let Some(network) = Network::presets().into_iter().find(|n| n.name == name) {
+                    self.custom = false;
+                    ctx.props().onchange.emit(Network {
+                        ledger_url: self.ledger_url(),
+                        ..network
+                    });
+                } else {
+                    self.custom = true;
+                }
+                true
+            }
+            NetworkCompMsg::HorizonUrl(horizon_url) => {
+                self.horizon_url = horizon_url;
+                false
+            }
+            NetworkCompMsg::Passphrase(passphrase) => {
+                self.passphrase = passphrase;
+                false
+            }
+            NetworkCompMsg::LedgerUrl(ledger_url) => {
+                self.ledger_url = ledger_url;
+                false
+            }
+            NetworkCompMsg::Use => {
+                let network = if self.custom {
+                    Network::custom(
+                        self.horizon_url.trim_end_matches('/').to_string(),
+                        self.passphrase.clone(),
+                        self.ledger_url(),
+                    )
+                } else {
+                    Network {
+                        ledger_url: self.ledger_url(),
+                        ..ctx.props().network.clone()
+                    }
+                };
+                ctx.props().onchange.emit(network);
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let scope = ctx.link();
+        let network = &ctx.props().network;
+        let onchange = scope.callback(|e: events::Event| NetworkCompMsg::Select {
+            name: e.target_unchecked_into::<HtmlSelectElement>().value(),
+        });
+        let oninput_ledger_url = scope.callback(|e: events::InputEvent| {
+            NetworkCompMsg::LedgerUrl(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onclick = scope.callback(|_| NetworkCompMsg::Use);
+        html! {
+            <span class="network">
+                <select {onchange}>
+                    {
+                        for Network::presets().iter().map(|n| {
+                            html! { <option value={n.name.clone()} selected={!self.custom && n.name == network.name}>{ &n.name }</option> }
+                        })
+                    }
+                    <option value="custom" selected={self.custom}>{ "custom" }</option>
+                </select>
+                {
+                    if self.custom {
+                        let oninput_url = scope.callback(|e: events::InputEvent| {
+                            NetworkCompMsg::HorizonUrl(e.target_unchecked_into::<HtmlInputElement>().value())
+                        });
+                        let oninput_passphrase = scope.callback(|e: events::InputEvent| {
+                            NetworkCompMsg::Passphrase(e.target_unchecked_into::<HtmlInputElement>().value())
+                        });
+                        html! {
+                            <>
+                                <input type="text" placeholder="horizon url" value={self.horizon_url.clone()} oninput={oninput_url} />
+                                <input type="text" placeholder="network passphrase" value={self.passphrase.clone()} oninput={oninput_passphrase} />
+                            </>
+                        }
+                    } else {
+                        html!()
+                    }
+                }
+                <input type="text" placeholder="ledger url" value={self.ledger_url.clone()} oninput={oninput_ledger_url} />
+                <button {onclick}>{ "use" }</button>
+            </span>
+        }
+    }
+}
+
+impl NetworkComp {
+    /// Returns the ledger URL entered, without a trailing slash.
+    fn ledger_url(&self) -> String {
+        self.ledger_url.trim().trim_end_matches('/').to_string()
+    }
+}
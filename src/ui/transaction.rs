@@ -0,0 +1,343 @@
+use crate::{
+    horizonapi,
+    network::Network,
+    stream::{decode_xdr, get_transaction, Error},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+use stellar_xdr::{
+    LedgerEntryChange, LedgerEntryChanges, OperationMeta, Transaction, TransactionEnvelope,
+    TransactionMeta, TransactionMetaV2, TransactionMetaV3, TransactionResult,
+    TransactionResultResult, TransactionV1Envelope, VecM, WriteXdr,
+};
+use yew::{
+    prelude::{html, Component, Context, Html},
+    Properties,
+};
+
+/// The transaction of an event, with its envelope, result, meta and fee meta
+/// decoded into collapsible trees.
+#[derive(Default)]
+pub struct TransactionComp {
+    network: Network,
+    hash: String,
+    trees: Option<Result<Vec<Result<Node, Error>>, Error>>,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TransactionCompProps {
+    pub network: Network,
+    pub hash: String,
+}
+
+pub enum TransactionCompMsg {
+    Fetched {
+        hash: String,
+        result: Result<horizonapi::transaction::Response, Error>,
+    },
+}
+
+impl Component for TransactionComp {
+    type Message = TransactionCompMsg;
+    type Properties = TransactionCompProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut comp = Self::default();
+        comp.fetch(ctx);
+        comp
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let props = ctx.props();
+        if self.network != props.network || self.hash != props.hash {
+            self.fetch(ctx);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            TransactionCompMsg::Fetched { hash, result } => {
+                if hash != self.hash {
+                    return false;
+                }
+                self.trees = Some(result.map(|tx| trees(&tx)));
+                true
+            }
+        }
+    }
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="component transaction">
+                <strong>{ "transaction: " }</strong>{ &self.hash }<br/>
+                {
+                    match &self.trees {
+                        None => html! { <>{ "loading" }</> },
+                        Some(Err(err)) => html! { <div class="error">{ format!("loading transaction failed: {err}") }</div> },
+                        Some(Ok(trees)) => html! {
+                            <>
+                                {
+                                    for trees.iter().map(|tree| match tree {
+                                        Ok(node) => view_node(node),
+                                        Err(err) => html! { <div class="error">{ err.to_string() }</div> },
+                                    })
+                                }
+                            </>
+                        },
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+impl TransactionComp {
+    fn fetch(&mut self, ctx: &Context<Self>) {
+        let props = ctx.props();
+        self.network = props.network.clone();
+        self.hash = props.hash.clone();
+        self.trees = None;
+        let base_url = props.network.horizon_url.clone();
+        let hash = props.hash.clone();
+        let link = ctx.link().clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = get_transaction(&base_url, &hash).await;
+            link.send_message(TransactionCompMsg::Fetched { hash, result });
+        });
+    }
+}
+
+/// A node of a tree of decoded XDR, with the XDR of the node as base64 if the
+/// node is a value that is encoded on its own. Nodes for the fields of such a
+/// value are decoded from its JSON, so they have no XDR of their own.
+struct Node {
+    label: String,
+    value: String,
+    xdr: Option<String>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    /// Returns the node of the value, with children for each of the fields
+    /// of the value.
+    fn new(label: impl Into<String>, v: &(impl WriteXdr + Serialize)) -> Self {
+        Self {
+            xdr: v.to_xdr_base64().ok(),
+            ..Self::json(label.into(), &serde_json::to_value(v).unwrap_or_default())
+        }
+    }
+
+    /// Returns the node of the value, with the children given.
+    fn with(
+        label: impl Into<String>,
+        v: &(impl WriteXdr + Serialize),
+        children: Vec<Node>,
+    ) -> Self {
+        Self {
+            children,
+            ..Self::new(label, v)
+        }
+    }
+
+    fn json(label: String, v: &Value) -> Self {
+        let (value, children) = match v {
+            Value::Object(o) => (
+                String::new(),
+                o.iter().map(|(k, v)| Self::json(k.clone(), v)).collect(),
+            ),
+            Value::Array(a) => (
+                format!("[{}]", a.len()),
+                a.iter()
+                    .enumerate()
+                    .map(|(i, v)| Self::json(i.to_string(), v))
+                    .collect(),
+            ),
+            Value::String(s) => (s.clone(), vec![]),
+            v => (v.to_string(), vec![]),
+        };
+        Self {
+            label,
+            value,
+            xdr: None,
+            children,
+        }
+    }
+}
+
+fn trees(tx: &horizonapi::transaction::Response) -> Vec<Result<Node, Error>> {
+    vec![
+        decode_xdr("envelope_xdr", tx.envelope_xdr.clone()).map(|e| envelope("envelope", &e)),
+        decode_xdr("result_xdr", tx.result_xdr.clone()).map(|r| result("result", &r)),
+        decode_xdr("result_meta_xdr", tx.result_meta_xdr.clone()).map(|m| meta("meta", &m)),
+        decode_xdr("fee_meta_xdr", tx.fee_meta_xdr.clone()).map(|c| changes("fee meta", &c)),
+    ]
+}
+
+fn envelope(label: &str, e: &TransactionEnvelope) -> Node {
+    match e {
+        TransactionEnvelope::Tx(TransactionV1Envelope { tx, signatures }) => Node::with(
+            label,
+            e,
+            vec![
+                transaction(tx),
+                Node::with(
+                    "signatures",
+                    signatures,
+                    signatures
+                        .iter()
+                        .enumerate()
+                        .map(|(i, s)| Node::new(format!("signature {i}"), s))
+                        .collect(),
+                ),
+            ],
+        ),
+        _ => Node::new(label, e),
+    }
+}
+
+fn transaction(tx: &Transaction) -> Node {
+    Node::with(
+        "transaction",
+        tx,
+        vec![
+            Node::new("source", &tx.source_account),
+            Node::new("sequence", &tx.seq_num),
+            Node::new("fee", &tx.fee),
+            Node::new("memo", &tx.memo),
+            Node::new("preconditions", &tx.cond),
+            Node::with(
+                "operations",
+                &tx.operations,
+                tx.operations
+                    .iter()
+                    .enumerate()
+                    .map(|(i, op)| Node::new(format!("operation {i}"), op))
+                    .collect(),
+            ),
+        ],
+    )
+}
+
+fn result(label: &str, r: &TransactionResult) -> Node {
+    let ops = match &r.result {
+        TransactionResultResult::TxSuccess(ops) | TransactionResultResult::TxFailed(ops) => ops
+            .iter()
+            .enumerate()
+            .map(|(i, op)| Node::new(format!("operation {i}"), op))
+            .collect(),
+        _ => vec![],
+    };
+    Node::with(
+        label,
+        r,
+        vec![
+            Node::new("fee charged", &r.fee_charged),
+            if ops.is_empty() {
+                Node::new("result", &r.result)
+            } else {
+                Node::with("result", &r.result, ops)
+            },
+        ],
+    )
+}
+
+fn meta(label: &str, m: &TransactionMeta) -> Node {
+    let children = match m {
+        TransactionMeta::V3(TransactionMetaV3 {
+            tx_changes_before,
+            operations,
+            tx_changes_after,
+            events,
+            ..
+        }) => vec![
+            changes("changes before", tx_changes_before),
+            operation_metas(operations),
+            changes("changes after", tx_changes_after),
+            Node::with(
+                "events",
+                events,
+                events
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| Node::new(format!("event {i}"), e))
+                    .collect(),
+            ),
+        ],
+        TransactionMeta::V2(TransactionMetaV2 {
+            tx_changes_before,
+            operations,
+            tx_changes_after,
+        }) => vec![
+            changes("changes before", tx_changes_before),
+            operation_metas(operations),
+            changes("changes after", tx_changes_after),
+        ],
+        _ => return Node::new(label, m),
+    };
+    Node::with(label, m, children)
+}
+
+fn operation_metas(operations: &VecM<OperationMeta>) -> Node {
+    Node::with(
+        "operations",
+        operations,
+        operations
+            .iter()
+            .enumerate()
+            .map(|(i, op)| changes(&format!("operation {i} changes"), &op.changes))
+            .collect(),
+    )
+}
+
+/// Returns the node of the ledger entry changes, labelling the state of an
+/// entry before it changed and the entry after it changed.
+fn changes(label: &str, c: &LedgerEntryChanges) -> Node {
+    Node::with(
+        label,
+        c,
+        c.0.iter()
+            .enumerate()
+            .map(|(i, change)| {
+                let kind = match change {
+                    LedgerEntryChange::Created(_) => "created",
+                    LedgerEntryChange::Updated(_) => "after",
+                    LedgerEntryChange::Removed(_) => "removed",
+                    LedgerEntryChange::State(_) => "before",
+                };
+                Node::new(format!("{i} {kind}"), change)
+            })
+            .collect(),
+    )
+}
+
+fn view_node(node: &Node) -> Html {
+    let xdr = match &node.xdr {
+        Some(xdr) => html! {
+            <details class="xdr">
+                <summary>{ "xdr" }</summary>
+                <code>{ xdr }</code>
+            </details>
+        },
+        None => html!(),
+    };
+    if node.children.is_empty() {
+        html! {
+            <div class="node">
+                <strong>{ &node.label }{ ": " }</strong><code>{ &node.value }</code>
+                { xdr }
+            </div>
+        }
+    } else {
+        html! {
+            <details class="node">
+                <summary><strong>{ &node.label }</strong>{ " " }{ &node.value }</summary>
+                { xdr }
+                { for node.children.iter().map(view_node) }
+            </details>
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{de::DeserializeOwned, Serialize};
+use stellar_xdr::{
+    ContractEvent, LedgerEntry, LedgerEntryChanges, LedgerFootprint, LedgerKey, ReadXdr, ScVal,
+    TransactionEnvelope, TransactionMeta, TransactionResult, WriteXdr,
+};
+use web_sys::{HtmlSelectElement, HtmlTextAreaElement};
+use yew::{
+    events,
+    prelude::{html, Component, Context, Html},
+    TargetCast,
+};
+
+/// A playground for decoding base64 XDR into JSON, and encoding JSON back into
+/// base64 XDR, without any requests to the network.
+#[derive(Default)]
+pub struct XdrComp {
+    xdr_type: XdrType,
+    /// The type the XDR was decoded as, which is the type selected unless the
+    /// type is detected.
+    decoded_type: Option<XdrType>,
+    xdr: String,
+    json: String,
+    error: Option<String>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub enum XdrType {
+    Detect,
+    TransactionEnvelope,
+    TransactionResult,
+    TransactionMeta,
+    LedgerEntryChanges,
+    LedgerFootprint,
+    LedgerKey,
+    LedgerEntry,
+    ContractEvent,
+    ScVal,
+}
+
+impl XdrType {
+    pub fn all() -> &'static [XdrType] {
+        &[
+            XdrType::Detect,
+            XdrType::TransactionEnvelope,
+            XdrType::TransactionResult,
+            XdrType::TransactionMeta,
+            XdrType::LedgerEntryChanges,
+            XdrType::LedgerFootprint,
+            XdrType::LedgerKey,
+            XdrType::LedgerEntry,
+            XdrType::ContractEvent,
+            XdrType::ScVal,
+        ]
+    }
+
+    /// Decodes the base64 XDR as the type into JSON. If the type is to be
+    /// detected, each type is tried in turn, and the type that decoded it is
+    /// returned with the JSON.
+    fn decode(self, xdr: &str) -> Result<(XdrType, String), String> {
+        let xdr = xdr.trim();
+        let json = match self {
+            XdrType::Detect => {
+                return XdrType::all()
+                    .iter()
+                    .skip(1)
+                    .find_map(|t| t.decode(xdr).ok())
+                    .ok_or_else(|| "not any of the types".to_string())
+            }
+            XdrType::TransactionEnvelope => to_json::<TransactionEnvelope>(xdr),
+            XdrType::TransactionResult => to_json::<TransactionResult>(xdr),
+            XdrType::TransactionMeta => to_json::<TransactionMeta>(xdr),
+            XdrType::LedgerEntryChanges => to_json::<LedgerEntryChanges>(xdr),
+            XdrType::LedgerFootprint => to_json::<LedgerFootprint>(xdr),
+            XdrType::LedgerKey => to_json::<LedgerKey>(xdr),
+            XdrType::LedgerEntry => to_json::<LedgerEntry>(xdr),
+            XdrType::ContractEvent => to_json::<ContractEvent>(xdr),
+            XdrType::ScVal => to_json::<ScVal>(xdr),
+        }?;
+        Ok((self, json))
+    }
+
+    /// Encodes the JSON as the type into base64 XDR.
+    fn encode(self, json: &str) -> Result<String, String> {
+        match self {
+            XdrType::Detect => Err("select the type to encode as".to_string()),
+            XdrType::TransactionEnvelope => to_xdr::<TransactionEnvelope>(json),
+            XdrType::TransactionResult => to_xdr::<TransactionResult>(json),
+            XdrType::TransactionMeta => to_xdr::<TransactionMeta>(json),
+            XdrType::LedgerEntryChanges => to_xdr::<LedgerEntryChanges>(json),
+            XdrType::LedgerFootprint => to_xdr::<LedgerFootprint>(json),
+            XdrType::LedgerKey => to_xdr::<LedgerKey>(json),
+            XdrType::LedgerEntry => to_xdr::<LedgerEntry>(json),
+            XdrType::ContractEvent => to_xdr::<ContractEvent>(json),
+            XdrType::ScVal => to_xdr::<ScVal>(json),
+        }
+    }
+}
+
+impl FromStr for XdrType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "detect" => Ok(XdrType::Detect),
+            "TransactionEnvelope" => Ok(XdrType::TransactionEnvelope),
+            "TransactionResult" => Ok(XdrType::TransactionResult),
+            "TransactionMeta" => Ok(XdrType::TransactionMeta),
+            "LedgerEntryChanges" => Ok(XdrType::LedgerEntryChanges),
+            "LedgerFootprint" => Ok(XdrType::LedgerFootprint),
+            "LedgerKey" => Ok(XdrType::LedgerKey),
+            "LedgerEntry" => Ok(XdrType::LedgerEntry),
+            "ContractEvent" => Ok(XdrType::ContractEvent),
+            "ScVal" => Ok(XdrType::ScVal),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for XdrType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XdrType::Detect => write!(f, "detect"),
+            XdrType::TransactionEnvelope => write!(f, "TransactionEnvelope"),
+            XdrType::TransactionResult => write!(f, "TransactionResult"),
+            XdrType::TransactionMeta => write!(f, "TransactionMeta"),
+            XdrType::LedgerEntryChanges => write!(f, "LedgerEntryChanges"),
+            XdrType::LedgerFootprint => write!(f, "LedgerFootprint"),
+            XdrType::LedgerKey => write!(f, "LedgerKey"),
+            XdrType::LedgerEntry => write!(f, "LedgerEntry"),
+            XdrType::ContractEvent => write!(f, "ContractEvent"),
+            XdrType::ScVal => write!(f, "ScVal"),
+        }
+    }
+}
+
+impl Default for XdrType {
+    fn default() -> Self {
+        XdrType::Detect
+    }
+}
+
+pub enum XdrCompMsg {
+    SelectType { xdr_type: XdrType },
+    Xdr(String),
+    Json(String),
+}
+
+impl Component for XdrComp {
+    type Message = XdrCompMsg;
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self::default()
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            XdrCompMsg::SelectType { xdr_type } => {
+                self.xdr_type = xdr_type;
+                self.decode();
+            }
+            XdrCompMsg::Xdr(xdr) => {
+                self.xdr = xdr;
+                self.decode();
+            }
+            XdrCompMsg::Json(json) => {
+                self.json = json;
+                let xdr_type = self.decoded_type.unwrap_or(self.xdr_type);
+                match xdr_type.encode(&self.json) {
+                    Ok(xdr) => {
+                        self.xdr = xdr;
+                        self.error = None;
+                    }
+                    Err(err) => self.error = Some(format!("encoding as {xdr_type} failed: {err}")),
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let scope = ctx.link();
+        let onchange = scope.callback(|e: events::Event| XdrCompMsg::SelectType {
+            xdr_type: XdrType::from_str(&e.target_unchecked_into::<HtmlSelectElement>().value())
+                .unwrap_or_default(),
+        });
+        let oninput_xdr = scope.callback(|e: events::InputEvent| {
+            XdrCompMsg::Xdr(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        });
+        let oninput_json = scope.callback(|e: events::InputEvent| {
+            XdrCompMsg::Json(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        });
+        html! {
+            <div class="component xdr">
+                <strong>{ "type: " }</strong>
+                <select {onchange}>
+                    {
+                        for XdrType::all().iter().map(|t| {
+                            html! { <option value={t.to_string()} selected={t == &self.xdr_type}>{t}</option> }
+                        })
+                    }
+                </select>
+                {
+                    match (self.xdr_type, self.decoded_type) {
+                        (XdrType::Detect, Some(t)) => html! { <>{ " detected: " }{ t }</> },
+                        _ => html!(),
+                    }
+                }
+                <br/>
+                <strong>{ "xdr: " }</strong>{ " (base64)" }<br/>
+                <textarea class="xdr" oninput={oninput_xdr} value={self.xdr.clone()} />
+                <br/>
+                <strong>{ "json: " }</strong>{ " (edit to encode)" }<br/>
+                <textarea class="json" oninput={oninput_json} value={self.json.clone()} />
+                {
+                    match &self.error {
+                        Some(err) => html! { <div class="error">{ err }</div> },
+                        None => html!(),
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+impl XdrComp {
+    fn decode(&mut self) {
+        if self.xdr.trim().is_empty() {
+            self.decoded_type = None;
+            self.error = None;
+            return;
+        }
+        match self.xdr_type.decode(&self.xdr) {
+            Ok((t, json)) => {
+                self.decoded_type = Some(t);
+                self.json = json;
+                self.error = None;
+            }
+            Err(err) => {
+                self.decoded_type = None;
+                self.error = Some(format!("decoding as {} failed: {err}", self.xdr_type));
+            }
+        }
+    }
+}
+
+fn to_json<T: ReadXdr + Serialize>(xdr: &str) -> Result<String, String> {
+    let v = T::from_xdr_base64(xdr).map_err(|e| e.to_string())?;
+    serde_json::to_string_pretty(&v).map_err(|e| e.to_string())
+}
+
+fn to_xdr<T: WriteXdr + DeserializeOwned>(json: &str) -> Result<String, String> {
+    let v = serde_json::from_str::<T>(json).map_err(|e| e.to_string())?;
+    v.to_xdr_base64().map_err(|e| e.to_string())
+}
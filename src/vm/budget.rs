@@ -0,0 +1,33 @@
+use soroban_env_host::budget::{Budget, CostType};
+
+/// The resources consumed by an invocation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BudgetReport {
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+    /// The number of inputs charged for each cost type, such as the number
+    /// of WASM instructions executed or the number of bytes hashed.
+    pub inputs: Vec<(String, u64)>,
+}
+
+impl BudgetReport {
+    pub fn input(&self, cost_type: &str) -> Option<u64> {
+        self.inputs
+            .iter()
+            .find(|(t, _)| t == cost_type)
+            .map(|(_, i)| *i)
+    }
+}
+
+impl From<&Budget> for BudgetReport {
+    fn from(budget: &Budget) -> Self {
+        Self {
+            cpu_insns: budget.get_cpu_insns_count(),
+            mem_bytes: budget.get_mem_bytes_count(),
+            inputs: CostType::variants()
+                .iter()
+                .map(|t| (format!("{t:?}"), budget.get_input(*t)))
+                .collect(),
+        }
+    }
+}
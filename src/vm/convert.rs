@@ -0,0 +1,13 @@
+use soroban_env_host::xdr;
+
+/// Converts a value from the `stellar-xdr` version used by the app to the
+/// version used by `soroban-env-host`, by round tripping through XDR.
+pub fn to_host<T: stellar_xdr::WriteXdr, U: xdr::ReadXdr>(v: &T) -> Option<U> {
+    U::from_xdr_base64(v.to_xdr_base64().ok()?).ok()
+}
+
+/// Converts a value from the `stellar-xdr` version used by `soroban-env-host`
+/// to the version used by the app, by round tripping through XDR.
+pub fn from_host<T: xdr::WriteXdr, U: stellar_xdr::ReadXdr>(v: &T) -> Option<U> {
+    U::from_xdr_base64(v.to_xdr_base64().ok()?).ok()
+}
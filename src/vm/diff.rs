@@ -0,0 +1,231 @@
+use soroban_env_host::{
+    storage::SnapshotSource,
+    xdr::{LedgerKey, LedgerKeyContractData, ScStatic, ScVal},
+};
+
+use super::convert::from_host;
+use super::invoke::LedgerMap;
+
+/// A change to a ledger entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StorageChange {
+    Created {
+        key: stellar_xdr::LedgerKey,
+        after: stellar_xdr::LedgerEntry,
+    },
+    Updated {
+        key: stellar_xdr::LedgerKey,
+        before: stellar_xdr::LedgerEntry,
+        after: stellar_xdr::LedgerEntry,
+    },
+    Deleted {
+        key: stellar_xdr::LedgerKey,
+        before: stellar_xdr::LedgerEntry,
+    },
+}
+
+impl StorageChange {
+    pub fn key(&self) -> &stellar_xdr::LedgerKey {
+        match self {
+            StorageChange::Created { key, .. }
+            | StorageChange::Updated { key, .. }
+            | StorageChange::Deleted { key, .. } => key,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StorageChange::Created { .. } => "created",
+            StorageChange::Updated { .. } => "updated",
+            StorageChange::Deleted { .. } => "deleted",
+        }
+    }
+
+    pub fn before(&self) -> Option<&stellar_xdr::LedgerEntry> {
+        match self {
+            StorageChange::Created { .. } => None,
+            StorageChange::Updated { before, .. } | StorageChange::Deleted { before, .. } => {
+                Some(before)
+            }
+        }
+    }
+
+    pub fn after(&self) -> Option<&stellar_xdr::LedgerEntry> {
+        match self {
+            StorageChange::Created { after, .. } | StorageChange::Updated { after, .. } => {
+                Some(after)
+            }
+            StorageChange::Deleted { .. } => None,
+        }
+    }
+}
+
+/// Returns the changes to contract data between the entries in the snapshot
+/// before an invocation and the storage after it. Contract code is excluded,
+/// as are entries that were only read.
+pub fn changes(before: &dyn SnapshotSource, after: &LedgerMap) -> Vec<StorageChange> {
+    let iter = match after.iter() {
+        Ok(iter) => iter,
+        Err(_) => return vec![],
+    };
+    iter.filter(|(k, _)| {
+        matches!(
+            k.as_ref(),
+            LedgerKey::ContractData(LedgerKeyContractData { key, .. })
+                if key != &ScVal::Static(ScStatic::LedgerKeyContractCode)
+        )
+    })
+    .filter_map(|(k, after)| {
+        let key = from_host(k.as_ref())?;
+        let before = before.get(k).ok();
+        Some(match (before, after) {
+            (None, None) => return None,
+            (None, Some(after)) => StorageChange::Created {
+                key,
+                after: from_host(after.as_ref())?,
+            },
+            (Some(before), None) => StorageChange::Deleted {
+                key,
+                before: from_host(&before)?,
+            },
+            (Some(before), Some(after)) if before.data == after.data => return None,
+            (Some(before), Some(after)) => StorageChange::Updated {
+                key,
+                before: from_host(&before)?,
+                after: from_host(after.as_ref())?,
+            },
+        })
+    })
+    .collect()
+}
+
+/// Returns the changes to contract data in the ledger entry changes recorded
+/// in the meta of a transaction, pairing each update and removal with the
+/// state of the entry before it. Contract code is excluded.
+pub fn meta_changes(changes: &[stellar_xdr::LedgerEntryChange]) -> Vec<StorageChange> {
+    let mut states = Vec::<(stellar_xdr::LedgerKey, stellar_xdr::LedgerEntry)>::new();
+    let mut result = vec![];
+    for c in changes {
+        match c {
+            stellar_xdr::LedgerEntryChange::State(e) => {
+                if let Some(key) = contract_data_key(e) {
+                    states.push((key, e.clone()));
+                }
+            }
+            stellar_xdr::LedgerEntryChange::Created(e) => {
+                if let Some(key) = contract_data_key(e) {
+                    result.push(StorageChange::Created {
+                        key,
+                        after: e.clone(),
+                    });
+                }
+            }
+            // The meta records the state of an entry before every update and
+            // removal of it.
+            stellar_xdr::LedgerEntryChange::Updated(e) => {
+                if let Some(key) = contract_data_key(e) {
+                    if let Some(before) = take_state(&mut states, &key) {
+                        result.push(StorageChange::Updated {
+                            key,
+                            before,
+                            after: e.clone(),
+                        });
+                    }
+                }
+            }
+            stellar_xdr::LedgerEntryChange::Removed(key) => {
+                if let Some(before) = take_state(&mut states, key) {
+                    result.push(StorageChange::Deleted {
+                        key: key.clone(),
+                        before,
+                    });
+                }
+            }
+        }
+    }
+    result
+}
+
+fn take_state(
+    states: &mut Vec<(stellar_xdr::LedgerKey, stellar_xdr::LedgerEntry)>,
+    key: &stellar_xdr::LedgerKey,
+) -> Option<stellar_xdr::LedgerEntry> {
+    let i = states.iter().position(|(k, _)| k == key)?;
+    Some(states.swap_remove(i).1)
+}
+
+/// Returns the key of the entry if it is contract data other than code.
+fn contract_data_key(e: &stellar_xdr::LedgerEntry) -> Option<stellar_xdr::LedgerKey> {
+    match &e.data {
+        stellar_xdr::LedgerEntryData::ContractData(stellar_xdr::ContractDataEntry {
+            contract_id,
+            key,
+            ..
+        }) if key != &stellar_xdr::ScVal::Static(stellar_xdr::ScStatic::LedgerKeyContractCode) => {
+            Some(stellar_xdr::LedgerKey::ContractData(
+                stellar_xdr::LedgerKeyContractData {
+                    contract_id: contract_id.clone(),
+                    key: key.clone(),
+                },
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The differences between the footprint recorded by a simulation and the
+/// footprint of a transaction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FootprintDiff {
+    /// Keys read that are not in the footprint of the transaction.
+    pub missing_read_only: Vec<stellar_xdr::LedgerKey>,
+    /// Keys written that are not read-write in the footprint of the
+    /// transaction.
+    pub missing_read_write: Vec<stellar_xdr::LedgerKey>,
+    /// Keys in the footprint of the transaction that were not accessed.
+    pub unused: Vec<stellar_xdr::LedgerKey>,
+}
+
+impl FootprintDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing_read_only.is_empty()
+            && self.missing_read_write.is_empty()
+            && self.unused.is_empty()
+    }
+}
+
+/// Compares the recorded footprint to the footprint of a transaction. A key
+/// read can be in either part of the transaction's footprint, but a key
+/// written must be read-write.
+pub fn footprint_diff(
+    recorded: &stellar_xdr::LedgerFootprint,
+    transaction: &stellar_xdr::LedgerFootprint,
+) -> FootprintDiff {
+    let in_transaction = |k: &stellar_xdr::LedgerKey| {
+        transaction.read_only.contains(k) || transaction.read_write.contains(k)
+    };
+    let in_recorded = |k: &stellar_xdr::LedgerKey| {
+        recorded.read_only.contains(k) || recorded.read_write.contains(k)
+    };
+    FootprintDiff {
+        missing_read_only: recorded
+            .read_only
+            .iter()
+            .filter(|k| !in_transaction(*k))
+            .cloned()
+            .collect(),
+        missing_read_write: recorded
+            .read_write
+            .iter()
+            .filter(|k| !transaction.read_write.contains(k))
+            .cloned()
+            .collect(),
+        unused: transaction
+            .read_only
+            .iter()
+            .chain(transaction.read_write.iter())
+            .filter(|k| !in_recorded(*k))
+            .cloned()
+            .collect(),
+    }
+}
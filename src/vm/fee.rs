@@ -0,0 +1,95 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// The rates a network charges for the resources a transaction uses, in
+/// stroops.
+///
+/// Horizon does not expose a network's rates, so the defaults are assumed and
+/// may differ from what a network actually charges.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeeRates {
+    /// The fee for including an operation in a ledger.
+    pub base: i64,
+    pub per_10k_cpu_insns: i64,
+    pub per_1kb_mem: i64,
+    pub per_read_entry: i64,
+    pub per_write_entry: i64,
+    pub per_1kb_read: i64,
+    pub per_1kb_write: i64,
+    pub per_1kb_events: i64,
+    pub per_1kb_tx: i64,
+}
+
+impl Default for FeeRates {
+    fn default() -> Self {
+        Self {
+            base: 100,
+            per_10k_cpu_insns: 25,
+            per_1kb_mem: 10,
+            per_read_entry: 6_250,
+            per_write_entry: 10_000,
+            per_1kb_read: 1_786,
+            per_1kb_write: 11_800,
+            per_1kb_events: 10_000,
+            per_1kb_tx: 16_235,
+        }
+    }
+}
+
+/// The resources used by an invocation that a network charges for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Resources {
+    pub cpu_insns: u64,
+    pub mem_bytes: u64,
+    /// The number of entries in the footprint, which are all read.
+    pub read_entries: u64,
+    /// The number of read-write entries in the footprint.
+    pub write_entries: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub events_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+/// The fee estimated for the resources of an invocation, broken down by what
+/// each resource contributes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeEstimate {
+    pub resources: Resources,
+    pub parts: Vec<(&'static str, i64)>,
+}
+
+impl FeeEstimate {
+    pub fn total(&self) -> i64 {
+        self.parts.iter().map(|(_, fee)| fee).sum()
+    }
+}
+
+pub fn estimate(resources: &Resources, rates: &FeeRates) -> FeeEstimate {
+    let r = resources;
+    FeeEstimate {
+        resources: r.clone(),
+        parts: vec![
+            ("base", rates.base),
+            ("cpu", per(r.cpu_insns, 10_000, rates.per_10k_cpu_insns)),
+            ("mem", per(r.mem_bytes, 1024, rates.per_1kb_mem)),
+            ("read entries", per(r.read_entries, 1, rates.per_read_entry)),
+            (
+                "write entries",
+                per(r.write_entries, 1, rates.per_write_entry),
+            ),
+            ("read bytes", per(r.read_bytes, 1024, rates.per_1kb_read)),
+            ("write bytes", per(r.write_bytes, 1024, rates.per_1kb_write)),
+            ("events", per(r.events_bytes, 1024, rates.per_1kb_events)),
+            ("tx size", per(r.tx_bytes, 1024, rates.per_1kb_tx)),
+        ],
+    }
+}
+
+/// Returns the rate charged for every unit of the amount, rounding partial
+/// units up.
+fn per(amount: u64, unit: u64, rate: i64) -> i64 {
+    let units = (amount + unit - 1) / unit;
+    i64::try_from(units)
+        .unwrap_or(i64::MAX)
+        .saturating_mul(rate)
+}
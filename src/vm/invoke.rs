@@ -1,59 +1,113 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 use soroban_env_host::{
     budget::Budget,
-    storage::{SnapshotSource, Storage},
+    events::{Events, HostEvent},
+    storage::{AccessType, Footprint, SnapshotSource, Storage},
     xdr::{
         self, AccountId, ContractDataEntry, Hash, HostFunction, LedgerEntry, LedgerEntryData,
         LedgerEntryExt, LedgerKey, LedgerKeyContractData, PublicKey, ScContractCode,
-        ScHostStorageErrorCode, ScObject, ScStatic, ScStatus, ScVal, Uint256,
+        ScHostStorageErrorCode, ScObject, ScStatic, ScStatus, ScVal, ScVec, Uint256, WriteXdr,
     },
-    Host, HostError, MeteredOrdMap, Status, events::Events,
+    Host, HostError, LedgerInfo, MeteredOrdMap, Status,
 };
 
+use super::convert::from_host;
+use super::diff::{changes, StorageChange};
+use super::fee::{estimate, FeeEstimate, FeeRates, Resources};
+use super::trace::{event_entries, TraceEntry};
+
+pub type LedgerMap = MeteredOrdMap<Box<LedgerKey>, Option<Box<LedgerEntry>>>;
+
+pub struct Invoked {
+    pub result: Result<ScVal, HostError>,
+    pub storage: LedgerMap,
+    /// The changes to contract data made by the invocation.
+    pub changes: Vec<StorageChange>,
+    /// The footprint of the ledger entries accessed by the invocation.
+    pub footprint: Option<stellar_xdr::LedgerFootprint>,
+    /// The fee estimated for submitting the invocation in a transaction.
+    pub fee: FeeEstimate,
+    pub budget: Budget,
+    pub events: Events,
+    /// Ids of the contracts called, in the order they were first called,
+    /// starting with the invoked contract.
+    pub calls: Vec<String>,
+    /// Ids of the contracts called that had no known code.
+    pub missing: Vec<String>,
+    /// The call, the contracts loaded, the debug and contract events, and
+    /// the return of the invocation. The host does not report the host
+    /// functions called, so loads and events are each in the order they
+    /// occurred, but not interleaved with each other.
+    pub trace: Vec<TraceEntry>,
+}
+
+/// The environment that invocations are simulated in.
+#[derive(Clone)]
+pub struct Config {
+    pub network_passphrase: String,
+    pub source_account: [u8; 32],
+    /// The sequence number and close time of the ledger the invocation is
+    /// simulated in.
+    pub sequence_number: u32,
+    pub timestamp: u64,
+    pub fee_rates: FeeRates,
+}
+
+/// Invokes the function on the contract with the id, with the code of every
+/// contract in contracts available so that it may call other contracts.
 pub fn invoke(
-    source: Option<MeteredOrdMap<Box<LedgerKey>, Option<Box<LedgerEntry>>>>,
-    code: Vec<u8>,
+    config: &Config,
+    source: Option<Box<dyn SnapshotSource>>,
+    contracts: Vec<(String, Vec<u8>)>,
     id: String,
     function: String,
     args: Vec<ScVal>,
-) -> (
-    String,
-    MeteredOrdMap<Box<LedgerKey>, Option<Box<LedgerEntry>>>,
-    Budget,
-    Events,
-) {
+) -> Invoked {
+    let call = TraceEntry::Call {
+        contract: id.clone(),
+        function: function.clone(),
+        args: args.iter().filter_map(from_host).collect(),
+    };
     let hex_id = hex::decode(&id).unwrap();
-    let mut sources: Vec<Box<dyn SnapshotSource>> = vec![Box::new(CodeOnlySnapshotSource(
-        (&hex_id).try_into().unwrap(),
-        ScContractCode::Wasm(code.try_into().unwrap()),
-    ))];
+    let code_source = CodeSnapshotSource::new(contracts);
+    let calls = code_source.calls.clone();
+    let mut sources: Vec<Box<dyn SnapshotSource>> = vec![Box::new(code_source)];
     if let Some(incoming_source) = source {
-        sources.push(Box::new(MemorySnapshotSource(incoming_source)));
+        sources.push(incoming_source);
     }
-    let source = MultiSnapshotSource(sources);
-    let storage = Storage::with_recording_footprint(Rc::new(source));
+    let source = Rc::new(MultiSnapshotSource(sources));
+    let storage = Storage::with_recording_footprint(source.clone());
     let h = Host::with_storage_and_budget(storage, Budget::default());
-    h.set_source_account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([0; 32]))));
-    let result = h.invoke_function(
-        HostFunction::InvokeContract,
-        [
-            vec![
-                ScVal::Object(Some(ScObject::Bytes(hex_id.try_into().unwrap()))),
-                ScVal::Symbol((&function).try_into().unwrap()),
-            ],
-            args,
-        ]
-        .concat()
-        .try_into()
-        .unwrap(),
-    );
-    let result_str = match result {
-        Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
-        Err(err) => err.to_string(),
-    };
-
-    let (Storage { map: storage, .. }, budget, events) = h
+    h.set_source_account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+        config.source_account,
+    ))));
+    h.set_ledger_info(LedgerInfo {
+        protocol_version: 0,
+        sequence_number: config.sequence_number,
+        timestamp: config.timestamp,
+        network_passphrase: config.network_passphrase.as_bytes().to_vec(),
+    });
+    let parameters: ScVec = [
+        vec![
+            ScVal::Object(Some(ScObject::Bytes(hex_id.try_into().unwrap()))),
+            ScVal::Symbol((&function).try_into().unwrap()),
+        ],
+        args,
+    ]
+    .concat()
+    .try_into()
+    .unwrap();
+    let result = h.invoke_function(HostFunction::InvokeContract, parameters.clone());
+    let (
+        Storage {
+            footprint,
+            map: storage,
+            ..
+        },
+        budget,
+        events,
+    ) = h
         .try_finish()
         .map_err(|_h| {
             HostError::from(ScStatus::HostStorageError(
@@ -62,7 +116,111 @@ pub fn invoke(
         })
         .unwrap();
 
-    (result_str, storage, budget, events)
+    let changes = changes(source.as_ref(), &storage);
+    let Calls {
+        called,
+        missing,
+        loads,
+    } = calls.take();
+    let resources = resources(
+        source.as_ref(),
+        &footprint,
+        &storage,
+        &budget,
+        &events,
+        &parameters,
+    );
+    let fee = estimate(&resources, &config.fee_rates);
+    let footprint = ledger_footprint(&footprint);
+    let mut trace = vec![call];
+    trace.extend(
+        loads
+            .into_iter()
+            .map(|(contract, missing)| TraceEntry::Load { contract, missing }),
+    );
+    trace.extend(event_entries(&events));
+    trace.push(TraceEntry::Return(match &result {
+        Ok(v) => from_host(v).ok_or_else(|| "result could not be converted".to_string()),
+        Err(err) => Err(err.to_string()),
+    }));
+    Invoked {
+        result,
+        storage,
+        changes,
+        footprint,
+        fee,
+        budget,
+        events,
+        calls: called,
+        missing,
+        trace,
+    }
+}
+
+/// The size of the parts of a transaction envelope with a single signature
+/// that invokes a host function, excluding the parameters and the keys of the
+/// footprint.
+const TX_OVERHEAD_BYTES: u64 = 164;
+
+/// Returns the resources used by an invocation. Must be called after the
+/// calls have been taken from the code source, since reading the footprint
+/// loads the code of the contracts again.
+fn resources(
+    source: &dyn SnapshotSource,
+    footprint: &Footprint,
+    storage: &LedgerMap,
+    budget: &Budget,
+    events: &Events,
+    parameters: &ScVec,
+) -> Resources {
+    let mut r = Resources {
+        cpu_insns: budget.get_cpu_insns_count(),
+        mem_bytes: budget.get_mem_bytes_count(),
+        tx_bytes: TX_OVERHEAD_BYTES + xdr_len(parameters),
+        ..Resources::default()
+    };
+    if let Ok(iter) = footprint.0.iter() {
+        for (k, access) in iter {
+            r.read_entries += 1;
+            r.tx_bytes += xdr_len(k.as_ref());
+            if let Ok(e) = source.get(k) {
+                r.read_bytes += xdr_len(&e);
+            }
+            if matches!(access, AccessType::ReadWrite) {
+                r.write_entries += 1;
+                if let Ok(Some(Some(e))) = storage.get(k) {
+                    r.write_bytes += xdr_len(e.as_ref());
+                }
+            }
+        }
+    }
+    for e in &events.0 {
+        if let HostEvent::Contract(e) = e {
+            r.events_bytes += xdr_len(e);
+        }
+    }
+    r
+}
+
+fn xdr_len(v: &impl WriteXdr) -> u64 {
+    v.to_xdr().map_or(0, |b| b.len() as u64)
+}
+
+/// Returns the footprint recorded by the storage of a host.
+fn ledger_footprint(footprint: &Footprint) -> Option<stellar_xdr::LedgerFootprint> {
+    let mut read_only = vec![];
+    let mut read_write = vec![];
+    for (k, access) in footprint.0.iter().ok()? {
+        let k = from_host(k.as_ref())?;
+        match access {
+            AccessType::ReadOnly => read_only.push(k),
+            AccessType::ReadWrite => read_write.push(k),
+        }
+    }
+    Some(stellar_xdr::LedgerFootprint {
+        read_only: read_only.try_into().ok()?,
+        read_write: read_write.try_into().ok()?,
+    })
 }
 
 struct MultiSnapshotSource(Vec<Box<dyn SnapshotSource>>);
@@ -87,55 +245,92 @@ impl SnapshotSource for MultiSnapshotSource {
     }
 }
 
-struct CodeOnlySnapshotSource(Hash, ScContractCode);
-impl CodeOnlySnapshotSource {
-    fn key(&self) -> LedgerKey {
-        LedgerKey::ContractData(LedgerKeyContractData {
-            contract_id: self.0.clone(),
-            key: ScVal::Static(ScStatic::LedgerKeyContractCode),
-        })
+#[derive(Default)]
+struct Calls {
+    called: Vec<String>,
+    missing: Vec<String>,
+    /// Ids of the contracts loaded, called and missing, in the order loaded,
+    /// and whether they were missing.
+    loads: Vec<(String, bool)>,
+}
+
+/// Provides the code of contracts, recording the contracts whose code is
+/// loaded, which happens the first time each contract is called.
+struct CodeSnapshotSource {
+    contracts: Vec<(Hash, ScContractCode)>,
+    calls: Rc<RefCell<Calls>>,
+}
+impl CodeSnapshotSource {
+    fn new(contracts: Vec<(String, Vec<u8>)>) -> Self {
+        Self {
+            contracts: contracts
+                .into_iter()
+                .filter_map(|(id, code)| {
+                    Some((
+                        Hash(hex::decode(id).ok()?.try_into().ok()?),
+                        ScContractCode::Wasm(code.try_into().ok()?),
+                    ))
+                })
+                .collect(),
+            calls: Rc::default(),
+        }
     }
-    fn data(&self) -> LedgerEntryData {
+    /// Returns the id of the contract if the key is for a contract's code.
+    fn contract_id(key: &LedgerKey) -> Option<&Hash> {
+        match key {
+            LedgerKey::ContractData(LedgerKeyContractData {
+                contract_id,
+                key: ScVal::Static(ScStatic::LedgerKeyContractCode),
+            }) => Some(contract_id),
+            _ => None,
+        }
+    }
+    fn data(id: &Hash, code: &ScContractCode) -> LedgerEntryData {
         LedgerEntryData::ContractData(ContractDataEntry {
-            contract_id: self.0.clone(),
+            contract_id: id.clone(),
             key: ScVal::Static(ScStatic::LedgerKeyContractCode),
-            val: ScVal::Object(Some(ScObject::ContractCode(self.1.clone()))),
+            val: ScVal::Object(Some(ScObject::ContractCode(code.clone()))),
         })
     }
 }
-impl SnapshotSource for CodeOnlySnapshotSource {
+impl SnapshotSource for CodeSnapshotSource {
     fn get(&self, key: &xdr::LedgerKey) -> Result<xdr::LedgerEntry, HostError> {
-        if key == &self.key() {
-            Ok(LedgerEntry {
-                last_modified_ledger_seq: 0,
-                data: self.data(),
-                ext: LedgerEntryExt::V0,
-            })
-        } else {
-            let status: Status =
-                ScStatus::HostStorageError(ScHostStorageErrorCode::UnknownError).into();
-            Err(status.into())
+        if let Some(id) = Self::contract_id(key) {
+            let mut calls = self.calls.borrow_mut();
+            if let Some((_, code)) = self.contracts.iter().find(|(i, _)| i == id) {
+                calls.called.push(hex::encode(id.0));
+                calls.loads.push((hex::encode(id.0), false));
+                return Ok(LedgerEntry {
+                    last_modified_ledger_seq: 0,
+                    data: Self::data(id, code),
+                    ext: LedgerEntryExt::V0,
+                });
+            }
+            calls.missing.push(hex::encode(id.0));
+            calls.loads.push((hex::encode(id.0), true));
         }
+        let status: Status =
+            ScStatus::HostStorageError(ScHostStorageErrorCode::UnknownError).into();
+        Err(status.into())
     }
     fn has(&self, key: &xdr::LedgerKey) -> Result<bool, HostError> {
-        Ok(key == &self.key())
+        Ok(Self::contract_id(key)
+            .map(|id| self.contracts.iter().any(|(i, _)| i == id))
+            .unwrap_or(false))
     }
 }
 
-struct MemorySnapshotSource(MeteredOrdMap<Box<LedgerKey>, Option<Box<LedgerEntry>>>);
-impl SnapshotSource for MemorySnapshotSource {
+pub struct LedgerSnapshotSource(pub Vec<(LedgerKey, LedgerEntry)>);
+impl SnapshotSource for LedgerSnapshotSource {
     fn get(&self, key: &xdr::LedgerKey) -> Result<xdr::LedgerEntry, HostError> {
-        if let Ok(Some(Some(le))) = self.0.get(key) {
-            return Ok(*le.clone());
+        if let Some((_, le)) = self.0.iter().find(|(k, _)| k == key) {
+            return Ok(le.clone());
         }
         let status: Status =
             ScStatus::HostStorageError(ScHostStorageErrorCode::UnknownError).into();
         Err(status.into())
     }
     fn has(&self, key: &xdr::LedgerKey) -> Result<bool, HostError> {
-        if let Ok(Some(Some(_))) = self.0.get(key) {
-            return Ok(true);
-        }
-        Ok(false)
+        Ok(self.0.iter().any(|(k, _)| k == key))
     }
 }
@@ -1 +1,7 @@
+pub mod budget;
+pub mod convert;
+pub mod diff;
+pub mod fee;
 pub mod invoke;
+pub mod replay;
+pub mod trace;
@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+
+use crate::stream::{operation_index, Contract, Event, EventBody, Invocation};
+use crate::strkey;
+
+use soroban_env_host::{
+    events::HostEvent,
+    storage::SnapshotSource,
+    xdr::{LedgerEntry, LedgerKey, ScHostStorageErrorCode, ScStatus},
+    HostError, Status,
+};
+use stellar_xdr::{
+    ContractEvent, InvokeHostFunctionResult, OperationResult, OperationResultTr, ScVal,
+    TransactionResultResult,
+};
+
+use super::convert::{from_host, to_host};
+use super::diff::{footprint_diff, FootprintDiff};
+use super::fee::FeeEstimate;
+use super::invoke::{invoke, Config, LedgerMap};
+
+pub struct ReplayStep {
+    pub op: String,
+    pub function: String,
+    pub outcome: ReplayOutcome,
+    /// The differences between the footprint recorded when replaying and the
+    /// footprint of the invocation on chain, if both are known.
+    pub footprint: Option<FootprintDiff>,
+    /// The fee estimated for the invocation when replaying.
+    pub fee: Option<FeeEstimate>,
+    /// The fee charged and the max fee of the invocation on chain.
+    pub fee_charged: Option<i64>,
+    pub max_fee: Option<i64>,
+}
+
+pub enum ReplayOutcome {
+    /// The invocation failed on chain and so never changed any state.
+    SkippedFailed,
+    /// One or more of the recorded args could not be decoded.
+    SkippedUndecodableArgs,
+    /// The invocation was replayed and its storage changes were kept.
+    Replayed { result: String },
+    /// The invocation errored when replayed and its storage changes were
+    /// discarded.
+    Errored { error: String },
+}
+
+/// The ledger entries written by the invocations replayed so far, with None
+/// for entries that were deleted. The storage of an invocation only holds the
+/// entries it accessed, so it is merged into the state rather than replacing
+/// it.
+#[derive(Clone, Default)]
+pub struct ReplayState(BTreeMap<LedgerKey, Option<LedgerEntry>>);
+
+impl ReplayState {
+    /// Merges the storage of an invocation into the state.
+    pub fn merge(&mut self, storage: &LedgerMap) {
+        if let Ok(iter) = storage.iter() {
+            self.apply(iter.map(|(k, v)| ((**k).clone(), v.as_ref().map(|e| (**e).clone()))));
+        }
+    }
+
+    fn apply(&mut self, changes: impl IntoIterator<Item = (LedgerKey, Option<LedgerEntry>)>) {
+        self.0.extend(changes);
+    }
+
+    /// Returns the entries that exist in the state, in order of key.
+    pub fn entries(&self) -> Vec<(LedgerKey, LedgerEntry)> {
+        self.0
+            .iter()
+            .filter_map(|(k, v)| Some((k.clone(), v.clone()?)))
+            .collect()
+    }
+}
+
+impl SnapshotSource for ReplayState {
+    fn get(&self, key: &LedgerKey) -> Result<LedgerEntry, HostError> {
+        if let Some(Some(le)) = self.0.get(key) {
+            return Ok(le.clone());
+        }
+        let status: Status =
+            ScStatus::HostStorageError(ScHostStorageErrorCode::UnknownError).into();
+        Err(status.into())
+    }
+    fn has(&self, key: &LedgerKey) -> Result<bool, HostError> {
+        Ok(matches!(self.0.get(key), Some(Some(_))))
+    }
+}
+
+/// Returns the id and code of the contract and of the deployments, for
+/// making every known contract available to be called by the contract.
+pub fn contract_codes(contract: &Contract, deployments: &[Contract]) -> Vec<(String, Vec<u8>)> {
+    let mut codes = vec![(contract.id.clone(), contract.bytes.clone())];
+    for d in deployments {
+        if !codes.iter().any(|(id, _)| id == &d.id) {
+            codes.push((d.id.clone(), d.bytes.clone()));
+        }
+    }
+    codes
+}
+
+/// Returns the TOID of the event, which orders operations by when they
+/// occurred. Ids are compared as numbers since they are not all the same
+/// length.
+fn toid(e: &Event) -> Option<u64> {
+    e.id.parse().ok()
+}
+
+/// Returns the config for the invocation, with the source account, ledger and
+/// close time it had on chain, where they are known.
+fn invocation_config(config: &Config, i: &Invocation) -> Config {
+    let mut config = config.clone();
+    if let Some(key) = i
+        .source_account
+        .as_deref()
+        .and_then(|a| strkey::decode_public_key(a).ok())
+    {
+        config.source_account = key;
+    }
+    if let Some(ledger) = i.ledger {
+        config.sequence_number = ledger;
+    }
+    if let Some(close_time) = i.close_time {
+        config.timestamp = close_time;
+    }
+    config
+}
+
+/// Returns whether the invocation in the event trapped on chain, which is how
+/// an invocation that errored in the host fails. None if its result is not
+/// known.
+fn trapped(event: &Event, i: &Invocation) -> Option<bool> {
+    if i.successful {
+        return Some(false);
+    }
+    match i.failure.as_ref()? {
+        TransactionResultResult::TxFailed(results) => Some(matches!(
+            results.get(operation_index(&event.id)?)?,
+            OperationResult::OpInner(OperationResultTr::InvokeHostFunction(
+                InvokeHostFunctionResult::Trapped
+            ))
+        )),
+        _ => Some(false),
+    }
+}
+
+/// Replays the invocations of the contract in the events, in the order they
+/// occurred, each with the function and args that were recorded on chain.
+/// Returns the storage resulting from the replay, and a step for each
+/// invocation describing what happened to it.
+pub fn replay(
+    config: &Config,
+    contract: &Contract,
+    events: &[Event],
+    deployments: &[Contract],
+) -> (Option<ReplayState>, Vec<ReplayStep>) {
+    let codes = contract_codes(contract, deployments);
+    let mut events = events.iter().collect::<Vec<_>>();
+    events.sort_by_key(|e| toid(e));
+
+    let mut storage: Option<ReplayState> = None;
+    let mut steps = vec![];
+    for e in events {
+        let i = match &e.body {
+            EventBody::Invocation(i) if i.id == contract.id => i,
+            _ => continue,
+        };
+        let mut footprint = None;
+        let mut fee = None;
+        let outcome = if !i.successful {
+            ReplayOutcome::SkippedFailed
+        } else if let Some(args) = i
+            .args
+            .iter()
+            .map(|a| a.as_ref().and_then(to_host))
+            .collect::<Option<Vec<_>>>()
+        {
+            let invoked = invoke(
+                &invocation_config(config, i),
+                storage
+                    .clone()
+                    .map(|s| Box::new(s) as Box<dyn SnapshotSource>),
+                codes.clone(),
+                contract.id.clone(),
+                i.function.clone(),
+                args,
+            );
+            if let (Some(recorded), Some(transaction)) = (&invoked.footprint, &i.footprint) {
+                footprint = Some(footprint_diff(recorded, transaction));
+            }
+            fee = Some(invoked.fee);
+            match invoked.result {
+                Ok(result) => {
+                    storage
+                        .get_or_insert_with(ReplayState::default)
+                        .merge(&invoked.storage);
+                    ReplayOutcome::Replayed {
+                        result: serde_json::to_string(&result).unwrap_or_default(),
+                    }
+                }
+                Err(err) => ReplayOutcome::Errored {
+                    error: err.to_string(),
+                },
+            }
+        } else {
+            ReplayOutcome::SkippedUndecodableArgs
+        };
+        steps.push(ReplayStep {
+            op: e.id.clone(),
+            function: i.function.clone(),
+            outcome,
+            footprint,
+            fee,
+            fee_charged: i.fee_charged,
+            max_fee: i.max_fee,
+        });
+    }
+    (storage, steps)
+}
+
+/// The outcome of re-running an invocation that is on chain.
+pub struct Rerun {
+    pub result: Result<ScVal, String>,
+    pub events: Vec<ContractEvent>,
+    /// Whether the result matches the result on chain, including whether the
+    /// invocation trapped.
+    pub result_matches: bool,
+    /// Whether the events match the events on chain, if those are known.
+    pub events_match: Option<bool>,
+}
+
+/// Re-runs the invocation in the event against the storage rebuilt by
+/// replaying the invocations of the contract before it, in the ledger and from
+/// the source account it had on chain, and compares the outcome with the
+/// outcome on chain.
+pub fn rerun(
+    config: &Config,
+    contract: &Contract,
+    event: &Event,
+    events: &[Event],
+    deployments: &[Contract],
+) -> Result<Rerun, String> {
+    let i = match &event.body {
+        EventBody::Invocation(i) if i.id == contract.id => i,
+        _ => return Err("event is not an invocation of the contract".to_string()),
+    };
+    let args = i
+        .args
+        .iter()
+        .map(|a| a.as_ref().and_then(to_host))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("one or more of the recorded args could not be decoded")?;
+    let earlier = events
+        .iter()
+        .filter(|e| toid(e) < toid(event))
+        .cloned()
+        .collect::<Vec<_>>();
+    let (storage, _) = replay(config, contract, &earlier, deployments);
+    let invoked = invoke(
+        &invocation_config(config, i),
+        storage.map(|s| Box::new(s) as Box<dyn SnapshotSource>),
+        contract_codes(contract, deployments),
+        contract.id.clone(),
+        i.function.clone(),
+        args,
+    );
+    let result = match invoked.result {
+        Ok(v) => from_host(&v).ok_or_else(|| "result could not be converted".to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+    let events = invoked
+        .events
+        .0
+        .iter()
+        .filter_map(|e| match e {
+            HostEvent::Contract(e) => from_host(e),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let result_matches = match (&result, &i.result) {
+        (Ok(v), Some(r)) => i.successful && v == r,
+        (Err(_), _) => trapped(event, i) == Some(true),
+        (Ok(_), None) => false,
+    };
+    // The events of a failed invocation are discarded, so only the failure
+    // needs to match.
+    let events_match = match (&result, &i.events) {
+        (Err(_), _) => trapped(event, i),
+        (Ok(_), Some(e)) => Some(e == &events),
+        (Ok(_), None) => None,
+    };
+    Ok(Rerun {
+        result,
+        events,
+        result_matches,
+        events_match,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use soroban_env_host::xdr::{
+        ContractDataEntry, Hash, LedgerEntryData, LedgerEntryExt, LedgerKeyContractData, ScVal,
+    };
+
+    use super::*;
+
+    fn entry(key: u32, val: u32) -> (LedgerKey, LedgerEntry) {
+        let contract_id = Hash([0; 32]);
+        (
+            LedgerKey::ContractData(LedgerKeyContractData {
+                contract_id: contract_id.clone(),
+                key: ScVal::U32(key),
+            }),
+            LedgerEntry {
+                last_modified_ledger_seq: 0,
+                data: LedgerEntryData::ContractData(ContractDataEntry {
+                    contract_id,
+                    key: ScVal::U32(key),
+                    val: ScVal::U32(val),
+                }),
+                ext: LedgerEntryExt::V0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_entries_of_earlier_invocations_survive() {
+        let (k1, e1) = entry(1, 10);
+        let (k2, e2) = entry(2, 20);
+        let mut state = ReplayState::default();
+        // Each invocation's storage only holds the keys it accessed.
+        state.apply([(k1.clone(), Some(e1.clone()))]);
+        state.apply([(k2.clone(), Some(e2.clone()))]);
+        assert_eq!(state.get(&k1).ok(), Some(e1));
+        assert_eq!(state.get(&k2).ok(), Some(e2));
+    }
+
+    #[test]
+    fn test_later_invocations_update_and_delete() {
+        let (k1, e1) = entry(1, 10);
+        let (_, e1_updated) = entry(1, 11);
+        let (k2, e2) = entry(2, 20);
+        let mut state = ReplayState::default();
+        state.apply([(k1.clone(), Some(e1)), (k2.clone(), Some(e2))]);
+        state.apply([(k1.clone(), Some(e1_updated.clone()))]);
+        state.apply([(k2.clone(), None)]);
+        assert_eq!(state.get(&k1).ok(), Some(e1_updated.clone()));
+        assert!(!state.has(&k2).unwrap());
+        assert!(state.get(&k2).is_err());
+        assert_eq!(state.entries(), vec![(k1, e1_updated)]);
+    }
+}
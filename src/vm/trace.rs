@@ -0,0 +1,39 @@
+use soroban_env_host::events::{Events, HostEvent};
+use stellar_xdr::{ContractEvent, ScVal};
+
+use super::convert::from_host;
+
+/// An entry in the log of an invocation. The host does not capture host
+/// function calls, so an entry is only recorded for what is observable from
+/// outside of the host.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEntry {
+    /// The function called on the contract by the simulation.
+    Call {
+        contract: String,
+        function: String,
+        args: Vec<ScVal>,
+    },
+    /// The code of a contract was loaded, which happens the first time it is
+    /// called. Missing is true if the contract had no known code.
+    Load {
+        contract: String,
+        missing: bool,
+    },
+    /// A debug event, such as a message logged by a contract.
+    Log(String),
+    Event(ContractEvent),
+    Return(Result<ScVal, String>),
+}
+
+/// Returns the entries for the events, in the order they occurred.
+pub fn event_entries(events: &Events) -> Vec<TraceEntry> {
+    events
+        .0
+        .iter()
+        .filter_map(|e| match e {
+            HostEvent::Contract(e) => Some(TraceEntry::Event(from_host(e)?)),
+            HostEvent::Debug(e) => Some(TraceEntry::Log(format!("{e:?}"))),
+        })
+        .collect()
+}